@@ -1,83 +1,493 @@
 use std::fs::File;
 
 use std::collections::HashMap;
-use std::io::{BufReader, Lines};
-use std::rc::Rc;
+use std::hash::Hash;
+use std::io::{BufRead, BufReader, Lines};
 
-use union_find::{Node, UnionFind};
+use union_find::DisjointSet;
 
-#[derive(Debug)]
-struct Edge {
-    src: usize,
-    target: usize,
-    distance: usize,
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edge {
+    pub src: usize,
+    pub target: usize,
+    pub distance: usize,
 }
 
-pub fn run_clustering_algo_with_huge_input(lines: &mut Lines<BufReader<File>>) -> usize {
-    let raw_input = lines.next().unwrap().unwrap();
-    let raw_headers: Vec<&str> = raw_input.split(" ").collect();
+impl Edge {
+    pub fn new(src: usize, target: usize, distance: usize) -> Self {
+        Edge {
+            src,
+            target,
+            distance,
+        }
+    }
+}
 
-    let nodes_count: usize = raw_headers[0].parse().unwrap();
-    let _size: usize = raw_headers[1].parse().unwrap();
-    let mut edges = init_huge_edges(lines);
-    run_clustering_algo(&mut edges, nodes_count, |t, _e| (nodes_count - t, false))
+#[derive(Debug, PartialEq, Eq)]
+pub struct Clustering {
+    // Cluster of every node, numbered by their smallest node.
+    pub assignments: Vec<usize>,
+    // Smallest distance between two clusters, None if no edge connects them.
+    pub spacing: Option<usize>,
+    // Edges that merged two clusters, in merge order. Each one joins the
+    // clusters of its ends at the height of its distance.
+    pub merges: Vec<Edge>,
 }
 
-pub fn run_clustering_algo_with_edge_input(
-    lines: &mut Lines<BufReader<File>>,
-    components: usize,
-) -> usize {
-    let raw_headers = lines.next().unwrap().unwrap();
+impl Clustering {
+    pub fn clusters_count(&self) -> usize {
+        self.assignments.iter().max().map_or(0, |&c| c + 1)
+    }
+}
 
-    let nodes_count: usize = raw_headers.parse().unwrap();
-    let mut edges = init_edges(lines);
-    run_clustering_algo(&mut edges, nodes_count, |t, e| {
-        (e.distance, t > nodes_count - components)
-    })
+// Single-link (Kruskal) clustering of nodes 0..n into k clusters,
+// O(m log m). Edges with equal distance merge in input order. Fewer than k
+// nodes can't be split into k clusters, and with too few edges more than k
+// clusters are left.
+pub fn single_link_clusters(edges: &[Edge], n: usize, k: usize) -> Clustering {
+    assert!(
+        k > 0 && k <= n.max(1),
+        "can't split {} nodes into {} clusters",
+        n,
+        k
+    );
+
+    let mut order: Vec<usize> = (0..edges.len()).collect();
+    order.sort_by_key(|&i| edges[i].distance);
+
+    let mut sets = DisjointSet::new(n);
+    let mut merges = Vec::with_capacity(n.saturating_sub(k));
+    let mut spacing = None;
+
+    for i in order {
+        let e = &edges[i];
+        if sets.same_set(e.src, e.target) {
+            continue;
+        }
+        if sets.count_sets() == k {
+            spacing = Some(e.distance);
+            break;
+        }
+
+        sets.union(e.src, e.target);
+        merges.push(e.clone());
+    }
+
+    Clustering {
+        assignments: assignments(&mut sets),
+        spacing,
+        merges,
+    }
 }
 
-fn run_clustering_algo<T, F>(edges: &mut Vec<Edge>, nodes_count: usize, f: F) -> T
-where
-    F: Fn(usize, &Edge) -> (T, bool),
-{
-    let mut node_map = build_node_map(edges, nodes_count);
+// Cluster of every node, numbered by their smallest node.
+fn assignments(sets: &mut DisjointSet) -> Vec<usize> {
+    let mut cluster_ids = vec![usize::MAX; sets.len()];
+    let mut next_id = 0;
 
-    let mut t = 0;
+    (0..sets.len())
+        .map(|v| {
+            let root = sets.find(v);
+            if cluster_ids[root] == usize::MAX {
+                cluster_ids[root] = next_id;
+                next_id += 1;
+            }
+            cluster_ids[root]
+        })
+        .collect()
+}
 
-    let mut result: Option<T> = None;
+// Leaves are clusters 0..leaves, merge i creates cluster leaves + i, like
+// in a SciPy linkage matrix.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DendrogramMerge {
+    // The smaller cluster id first.
+    pub left: usize,
+    pub right: usize,
+    pub height: f64,
+    // Leaves in the new cluster.
+    pub size: usize,
+}
+
+// Merge history of agglomerative clustering with non-decreasing heights.
+// Fewer than leaves - 1 merges means the input didn't connect everything.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dendrogram {
+    pub leaves: usize,
+    pub merges: Vec<DendrogramMerge>,
+}
 
-    for i in edges.iter() {
-        let src_node = fetch_node_by_id(&mut node_map, i.src);
-        let target_node = fetch_node_by_id(&mut node_map, i.target);
-        let src_v = *UnionFind::find_val(&src_node).borrow();
-        let target_v = *UnionFind::find_val(&target_node).borrow();
+impl Dendrogram {
+    // `pairs` are (any leaf of one cluster, any leaf of the other, height)
+    // in merge order.
+    fn from_leaf_pairs(leaves: usize, pairs: &[(usize, usize, f64)]) -> Self {
+        let mut sets = DisjointSet::new(leaves);
+        let mut cluster_ids: Vec<usize> = (0..leaves).collect();
+        let mut merges = Vec::with_capacity(pairs.len());
 
-        if src_v != target_v {
-            t += 1;
+        for (i, &(a, b, height)) in pairs.iter().enumerate() {
+            let (a_root, b_root) = (sets.find(a), sets.find(b));
+            let (a_id, b_id) = (cluster_ids[a_root], cluster_ids[b_root]);
+            sets.union(a_root, b_root);
 
-            UnionFind::union(&src_node, &target_node);
+            let root = sets.find(a_root);
+            cluster_ids[root] = leaves + i;
+            merges.push(DendrogramMerge {
+                left: a_id.min(b_id),
+                right: a_id.max(b_id),
+                height,
+                size: sets.set_size(root),
+            });
+        }
+
+        Dendrogram { leaves, merges }
+    }
+
+    // Leaf sets after applying the first `count` merges.
+    fn apply(&self, count: usize) -> DisjointSet {
+        let mut sets = DisjointSet::new(self.leaves);
+        // Some leaf of every cluster id.
+        let mut representatives: Vec<usize> = (0..self.leaves).collect();
+
+        for m in self.merges.iter() {
+            representatives.push(representatives[m.left]);
+        }
+        for m in self.merges.iter().take(count) {
+            sets.union(representatives[m.left], representatives[m.right]);
+        }
+
+        sets
+    }
 
-            let r = f(t, i);
-            result = Some(r.0);
-            if r.1 {
-                return result.unwrap();
+    // Cluster of every leaf once all merges up to `height` are made,
+    // numbered by their smallest leaf.
+    pub fn cut_at_height(&self, height: f64) -> Vec<usize> {
+        let count = self.merges.partition_point(|m| m.height <= height);
+        assignments(&mut self.apply(count))
+    }
+
+    // Cluster of every leaf with k clusters left, or as few as the merges
+    // allow, numbered by their smallest leaf.
+    pub fn cut_at_k(&self, k: usize) -> Vec<usize> {
+        assert!(k > 0, "can't cut into 0 clusters");
+        assignments(&mut self.apply(self.leaves.saturating_sub(k)))
+    }
+
+    // Number of clusters in min_k..=max_k cut just below the largest jump
+    // between consecutive merge heights, fewer clusters on equal jumps. None
+    // if no cut between two merges gives such a k.
+    pub fn largest_gap_k(&self, min_k: usize, max_k: usize) -> Option<usize> {
+        let mut best: Option<(f64, usize)> = None;
+
+        for (i, w) in self.merges.windows(2).enumerate() {
+            let k = self.leaves - i - 1;
+            let gap = w[1].height - w[0].height;
+            if k < min_k || k > max_k {
+                continue;
+            }
+            if best
+                .is_none_or(|(best_gap, best_k)| gap > best_gap || (gap == best_gap && k < best_k))
+            {
+                best = Some((gap, k));
             }
         }
+
+        best.map(|(_, k)| k)
     }
 
-    result.unwrap()
+    // Rows of [left, right, height, size], SciPy's linkage matrix format.
+    pub fn linkage_matrix(&self) -> Vec<[f64; 4]> {
+        self.merges
+            .iter()
+            .map(|m| [m.left as f64, m.right as f64, m.height, m.size as f64])
+            .collect()
+    }
 }
 
-fn build_node_map(edges: &mut Vec<Edge>, nodes_count: usize) -> HashMap<usize, Node<usize>> {
-    let mut node_map = HashMap::with_capacity(nodes_count);
+// Single-linkage hierarchical clustering, O(m log m): Kruskal run until
+// every edge is seen.
+pub fn single_linkage(edges: &[Edge], n: usize) -> Dendrogram {
+    let pairs: Vec<(usize, usize, f64)> = single_link_clusters(edges, n, 1)
+        .merges
+        .into_iter()
+        .map(|e| (e.src, e.target, e.distance as f64))
+        .collect();
+
+    Dendrogram::from_leaf_pairs(n, &pairs)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Linkage {
+    // Closest pair of points.
+    Single,
+    // Farthest pair of points.
+    Complete,
+    // Mean over all pairs of points (UPGMA).
+    Average,
+    // Increase of the within-cluster variance, for Euclidean distances.
+    Ward,
+}
+
+impl Linkage {
+    // Lance-Williams update: distance from k to the union of i and j.
+    fn update(&self, d_ki: f64, d_kj: f64, d_ij: f64, n_i: f64, n_j: f64, n_k: f64) -> f64 {
+        match self {
+            Linkage::Single => d_ki.min(d_kj),
+            Linkage::Complete => d_ki.max(d_kj),
+            Linkage::Average => (n_i * d_ki + n_j * d_kj) / (n_i + n_j),
+            Linkage::Ward => (((n_i + n_k) * d_ki * d_ki + (n_j + n_k) * d_kj * d_kj
+                - n_k * d_ij * d_ij)
+                / (n_i + n_j + n_k))
+                .sqrt(),
+        }
+    }
+}
+
+// Agglomerative clustering over a symmetric distance matrix with the
+// nearest-neighbour chain algorithm, O(n^2) time and memory. Follows the
+// chain of nearest neighbours until two clusters are each other's nearest
+// and merges them, which gives the same tree as always merging the globally
+// closest pair for all of these linkages. Ties go to the previous chain
+// element, then to the smaller index.
+pub fn linkage(distances: &[Vec<f64>], method: Linkage) -> Dendrogram {
+    let n = distances.len();
+    assert!(
+        distances.iter().all(|row| row.len() == n),
+        "distance matrix isn't square"
+    );
+
+    // A cluster lives in the slot of its smallest leaf.
+    let mut d: Vec<Vec<f64>> = distances.to_vec();
+    let mut sizes = vec![1.0; n];
+    let mut is_active = vec![true; n];
+    let mut chain: Vec<usize> = Vec::with_capacity(n);
+    let mut pairs = Vec::with_capacity(n.saturating_sub(1));
+
+    while pairs.len() + 1 < n {
+        if chain.is_empty() {
+            chain.push(is_active.iter().position(|&a| a).unwrap());
+        }
+
+        let a = chain[chain.len() - 1];
+        let prev = if chain.len() > 1 {
+            Some(chain[chain.len() - 2])
+        } else {
+            None
+        };
+
+        let mut nearest = prev;
+        for k in 0..n {
+            if k != a && is_active[k] && nearest.is_none_or(|b| d[a][k] < d[a][b]) {
+                nearest = Some(k);
+            }
+        }
+        let b = nearest.unwrap();
 
-    edges.sort_by(|a, b| a.distance.cmp(&b.distance));
+        if Some(b) != prev {
+            chain.push(b);
+            continue;
+        }
+
+        chain.truncate(chain.len() - 2);
+        let (i, j) = (a.min(b), a.max(b));
+        let d_ij = d[i][j];
+        pairs.push((i, j, d_ij));
 
-    for i in 0..nodes_count {
-        create_root(&mut node_map, i + 1);
+        for k in 0..n {
+            if is_active[k] && k != i && k != j {
+                let v = method.update(d[k][i], d[k][j], d_ij, sizes[i], sizes[j], sizes[k]);
+                d[k][i] = v;
+                d[i][k] = v;
+            }
+        }
+        sizes[i] += sizes[j];
+        is_active[j] = false;
     }
 
-    node_map
+    pairs.sort_by(|x, y| x.2.total_cmp(&y.2));
+    Dendrogram::from_leaf_pairs(n, &pairs)
+}
+
+fn euclidean(a: &[f64], b: &[f64]) -> f64 {
+    assert_eq!(a.len(), b.len(), "points of different dimensions");
+    a.iter()
+        .zip(b.iter())
+        .map(|(x, y)| (x - y) * (x - y))
+        .sum::<f64>()
+        .sqrt()
+}
+
+// Point indices of every non-empty cluster.
+fn cluster_members(assignments: &[usize]) -> Vec<Vec<usize>> {
+    let count = assignments.iter().max().map_or(0, |&c| c + 1);
+    let mut members = vec![Vec::new(); count];
+    for (i, &c) in assignments.iter().enumerate() {
+        members[c].push(i);
+    }
+    members.retain(|m| !m.is_empty());
+    members
+}
+
+// Mean silhouette over all points with Euclidean distances, O(n^2). In
+// [-1, 1], higher is better; a point alone in its cluster scores 0. None with
+// fewer than 2 clusters.
+pub fn silhouette_score(points: &[Vec<f64>], assignments: &[usize]) -> Option<f64> {
+    let members = cluster_members(assignments);
+    if members.len() < 2 {
+        return None;
+    }
+
+    let mut total = 0.0;
+    for (own, cluster) in members.iter().enumerate() {
+        if cluster.len() == 1 {
+            continue;
+        }
+
+        for &i in cluster.iter() {
+            let mean_distance = |other: &Vec<usize>| {
+                other
+                    .iter()
+                    .map(|&j| euclidean(&points[i], &points[j]))
+                    .sum::<f64>()
+            };
+
+            let a = mean_distance(cluster) / (cluster.len() - 1) as f64;
+            let b = members
+                .iter()
+                .enumerate()
+                .filter(|&(c, _)| c != own)
+                .map(|(_, other)| mean_distance(other) / other.len() as f64)
+                .fold(f64::INFINITY, f64::min);
+
+            if a.max(b) > 0.0 {
+                total += (b - a) / a.max(b);
+            }
+        }
+    }
+
+    Some(total / points.len() as f64)
+}
+
+// Smallest distance between points of different clusters over the largest
+// cluster diameter, O(n^2). Higher is better. None with fewer than 2 clusters
+// or when every cluster is a single location, as the ratio is undefined.
+pub fn dunn_index(points: &[Vec<f64>], assignments: &[usize]) -> Option<f64> {
+    if cluster_members(assignments).len() < 2 {
+        return None;
+    }
+
+    let mut separation = f64::INFINITY;
+    let mut diameter: f64 = 0.0;
+    for i in 0..points.len() {
+        for j in i + 1..points.len() {
+            let d = euclidean(&points[i], &points[j]);
+            if assignments[i] == assignments[j] {
+                diameter = diameter.max(d);
+            } else {
+                separation = separation.min(d);
+            }
+        }
+    }
+
+    if diameter == 0.0 {
+        return None;
+    }
+    Some(separation / diameter)
+}
+
+// Mean over clusters of the worst (s_i + s_j) / d(c_i, c_j), where c is a
+// centroid and s the mean distance to it, O(n * dim + k^2 * dim). Lower is
+// better. None with fewer than 2 clusters or when two centroids coincide.
+pub fn davies_bouldin_index(points: &[Vec<f64>], assignments: &[usize]) -> Option<f64> {
+    let members = cluster_members(assignments);
+    if members.len() < 2 {
+        return None;
+    }
+
+    let centroids: Vec<Vec<f64>> = members
+        .iter()
+        .map(|cluster| {
+            let mut centroid = vec![0.0; points[cluster[0]].len()];
+            for &i in cluster.iter() {
+                for (c, x) in centroid.iter_mut().zip(points[i].iter()) {
+                    *c += x;
+                }
+            }
+            centroid.iter().map(|c| c / cluster.len() as f64).collect()
+        })
+        .collect();
+
+    let scatters: Vec<f64> = members
+        .iter()
+        .zip(centroids.iter())
+        .map(|(cluster, centroid)| {
+            cluster
+                .iter()
+                .map(|&i| euclidean(&points[i], centroid))
+                .sum::<f64>()
+                / cluster.len() as f64
+        })
+        .collect();
+
+    let mut total = 0.0;
+    for i in 0..members.len() {
+        let mut worst: f64 = 0.0;
+        for j in 0..members.len() {
+            if j == i {
+                continue;
+            }
+            let d = euclidean(&centroids[i], &centroids[j]);
+            if d == 0.0 {
+                return None;
+            }
+            worst = worst.max((scatters[i] + scatters[j]) / d);
+        }
+        total += worst;
+    }
+
+    Some(total / members.len() as f64)
+}
+
+pub fn run_clustering_algo_with_huge_input<B: BufRead>(lines: &mut Lines<B>) -> usize {
+    let raw_input = lines.next().unwrap().unwrap();
+    let raw_headers: Vec<&str> = raw_input.split(" ").collect();
+
+    let nodes_count: usize = raw_headers[0].parse().unwrap();
+    let size: usize = raw_headers[1].parse().unwrap();
+    let labels = init_labels(lines);
+    assert!(
+        labels.len() == nodes_count,
+        "Expected {} labels, got {}",
+        nodes_count,
+        labels.len()
+    );
+    assert!(
+        labels.iter().all(|l| l.width() == size),
+        "Every label must have {} bits",
+        size
+    );
+    hamming_clusters(&labels, 2).count
+}
+
+pub fn run_clustering_algo_with_edge_input(
+    lines: &mut Lines<BufReader<File>>,
+    components: usize,
+) -> usize {
+    let raw_headers = lines.next().unwrap().unwrap();
+
+    let nodes_count: usize = raw_headers.parse().unwrap();
+    let edges = init_edges(lines);
+    let result = single_link_clusters(&edges, nodes_count, components);
+
+    // With no edge left between the clusters, e.g. for a single component,
+    // the distance of the last merge is returned instead.
+    match result.spacing {
+        Some(v) => v,
+        None => match result.merges.last() {
+            Some(e) => e.distance,
+            None => panic!("No edge merges two of {} clusters", components),
+        },
+    }
 }
 
 fn init_edges(lines: &mut Lines<BufReader<File>>) -> Vec<Edge> {
@@ -90,9 +500,10 @@ fn init_edges(lines: &mut Lines<BufReader<File>>) -> Vec<Edge> {
             let target: usize = raw_data[1].parse().unwrap();
             let distance: usize = raw_data[2].parse().unwrap();
 
+            // The file numbers vertices from 1.
             edges.push(Edge {
-                src,
-                target,
+                src: src - 1,
+                target: target - 1,
                 distance,
             })
         }
@@ -101,93 +512,264 @@ fn init_edges(lines: &mut Lines<BufReader<File>>) -> Vec<Edge> {
     edges
 }
 
-fn init_huge_edges(lines: &mut Lines<BufReader<File>>) -> Vec<Edge> {
-    let mut edges = Vec::new();
-    let mut edges_map = HashMap::new();
-    let mut vertexes = Vec::new();
+fn init_labels<B: BufRead>(lines: &mut Lines<B>) -> Vec<BitLabel> {
+    let mut labels = Vec::new();
 
-    let mut line_pointer: usize = 0;
-    for line_res in lines {
-        if let Ok(line) = line_res {
-            let mut values = [0u8; 24];
-            let mut cursor = 0;
-            for i in line.split(" ") {
-                if let Ok(s) = i.parse::<u8>() {
-                    values[cursor] = s;
-                    cursor += 1;
-                }
-            }
+    for line in lines.map_while(Result::ok) {
+        let bits: Vec<bool> = line
+            .split(" ")
+            .filter_map(|i| i.parse::<u8>().ok())
+            .map(|b| b == 1)
+            .collect();
+
+        labels.push(BitLabel::from_bits(&bits));
+    }
+
+    labels
+}
+
+// A fixed-width bit string compared by Hamming distance.
+pub trait HammingLabel: Clone + Eq + Hash {
+    fn width(&self) -> usize;
+
+    fn bit(&self, bit: usize) -> bool;
 
-            vertexes.push(values);
-            edges_map.entry(values).or_insert(vec![]).push(line_pointer);
-            line_pointer += 1;
+    fn flip(&mut self, bit: usize);
+
+    fn distance(&self, other: &Self) -> usize;
+}
+
+impl HammingLabel for u64 {
+    fn width(&self) -> usize {
+        u64::BITS as usize
+    }
+
+    fn bit(&self, bit: usize) -> bool {
+        *self >> bit & 1 == 1
+    }
+
+    fn flip(&mut self, bit: usize) {
+        *self ^= 1 << bit;
+    }
+
+    fn distance(&self, other: &Self) -> usize {
+        (self ^ other).count_ones() as usize
+    }
+}
+
+impl HammingLabel for u128 {
+    fn width(&self) -> usize {
+        u128::BITS as usize
+    }
+
+    fn bit(&self, bit: usize) -> bool {
+        *self >> bit & 1 == 1
+    }
+
+    fn flip(&mut self, bit: usize) {
+        *self ^= 1 << bit;
+    }
+
+    fn distance(&self, other: &Self) -> usize {
+        (self ^ other).count_ones() as usize
+    }
+}
+
+// Bit string of any width packed into u64 words.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitLabel {
+    width: usize,
+    words: Vec<u64>,
+}
+
+impl BitLabel {
+    pub fn new(width: usize) -> Self {
+        BitLabel {
+            width,
+            words: vec![0; width.div_ceil(64)],
         }
     }
 
-    let mut id = 0;
-    for v in &vertexes {
-        let zero_distance_v = edges_map.get(v).unwrap();
-        for i in zero_distance_v {
-            if *i != id {
-                edges.push(Edge {
-                    src: id + 1,
-                    target: i + 1,
-                    distance: 0,
-                })
-            }
+    pub fn from_bits(bits: &[bool]) -> Self {
+        let mut label = BitLabel::new(bits.len());
+        for (i, &bit) in bits.iter().enumerate() {
+            label.set(i, bit);
         }
-        for i in produce_neighbours(v) {
-            if let Some(neighbours) = edges_map.get(&i.0) {
-                for n in neighbours {
-                    edges.push(Edge {
-                        src: id + 1,
-                        target: n + 1,
-                        distance: i.1,
-                    })
-                }
+        label
+    }
+
+    pub fn get(&self, bit: usize) -> bool {
+        self.words[bit / 64] & (1 << (bit % 64)) != 0
+    }
+
+    pub fn set(&mut self, bit: usize, value: bool) {
+        if self.get(bit) != value {
+            self.flip(bit);
+        }
+    }
+}
+
+impl HammingLabel for BitLabel {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn bit(&self, bit: usize) -> bool {
+        self.get(bit)
+    }
+
+    fn flip(&mut self, bit: usize) {
+        assert!(bit < self.width, "bit {} out of {}", bit, self.width);
+        self.words[bit / 64] ^= 1 << (bit % 64);
+    }
+
+    fn distance(&self, other: &Self) -> usize {
+        assert_eq!(self.width, other.width, "labels of different widths");
+        self.words
+            .iter()
+            .zip(other.words.iter())
+            .map(|(a, b)| (a ^ b).count_ones() as usize)
+            .sum()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct HammingClusters {
+    pub count: usize,
+    // Cluster of every label, numbered by their first label.
+    pub assignments: Vec<usize>,
+}
+
+// Clusters labels so that two labels at Hamming distance at most d always
+// share a cluster, i.e. single-link clustering with every edge up to d
+// taken. Picks the cheaper of two searches over the distinct labels:
+// looking up all sum(C(width, i), i = 1..=d) neighbours of every label in a
+// hash map (300 for 24 bits and d = 2), or multi-index hashing, which only
+// compares labels that agree exactly on one of d + 1 blocks (about 350k
+// neighbours against a handful of candidates for 128 bits and d = 3).
+pub fn hamming_clusters<L: HammingLabel>(labels: &[L], d: usize) -> HammingClusters {
+    let mut sets = DisjointSet::new(labels.len());
+    let mut first_seen: HashMap<&L, usize> = HashMap::with_capacity(labels.len());
+    let mut distinct = Vec::new();
+
+    for (i, label) in labels.iter().enumerate() {
+        match first_seen.get(label) {
+            Some(&first) => {
+                sets.union(first, i);
+            }
+            None => {
+                first_seen.insert(label, i);
+                distinct.push(i);
             }
         }
-        id += 1;
     }
 
-    edges
+    let width = labels.first().map_or(0, |l| l.width());
+    if neighbours_count(width, d) <= block_candidates(width, d, distinct.len()) {
+        link_neighbours(&first_seen, d, &mut sets);
+    } else {
+        link_by_blocks(labels, &distinct, d, &mut sets);
+    }
+
+    HammingClusters {
+        count: sets.count_sets(),
+        assignments: assignments(&mut sets),
+    }
+}
+
+// Labels within distance 1..=d of one label, saturating.
+fn neighbours_count(width: usize, d: usize) -> usize {
+    let mut binomial: usize = 1;
+    let mut total: usize = 0;
+    for i in 1..=d.min(width) {
+        binomial = binomial.saturating_mul(width - i + 1) / i;
+        total = total.saturating_add(binomial);
+    }
+    total
+}
+
+// Expected candidates per label with multi-index hashing on uniformly
+// random labels: n / 2^block_width for each of the d + 1 blocks.
+fn block_candidates(width: usize, d: usize, n: usize) -> usize {
+    let block_width = (width / (d + 1)).min(63);
+    (d + 1).saturating_mul(n >> block_width).max(1)
+}
+
+fn link_neighbours<L: HammingLabel>(
+    first_seen: &HashMap<&L, usize>,
+    d: usize,
+    sets: &mut DisjointSet,
+) {
+    for (label, &i) in first_seen.iter() {
+        let mut neighbour = (*label).clone();
+        for_each_neighbour(&mut neighbour, 0, d, &mut |n| {
+            if let Some(&j) = first_seen.get(n) {
+                sets.union(i, j);
+            }
+        });
+    }
 }
 
-fn produce_neighbours(origin: &[u8; 24]) -> [([u8; 24], usize); 300] {
-    let mut neighbors = [(*origin, 1); 300];
+// Labels within distance d differ in at most d of d + 1 disjoint blocks, so
+// they agree exactly on at least one. Every block buckets the labels by its
+// bits and only labels sharing a bucket are compared.
+fn link_by_blocks<L: HammingLabel>(
+    labels: &[L],
+    distinct: &[usize],
+    d: usize,
+    sets: &mut DisjointSet,
+) {
+    let width = labels.first().map_or(0, |l| l.width());
+    let blocks = d + 1;
 
-    let mut pointer = 0;
-    for i in 0..25 {
-        for j in 0..i {
-            if i < 24 {
-                neighbors[pointer].0[i] = (neighbors[pointer].0[i] + 1) % 2;
-                neighbors[pointer].1 = 2;
+    for b in 0..blocks {
+        let (start, end) = (b * width / blocks, (b + 1) * width / blocks);
+        let mut buckets: HashMap<Vec<u64>, Vec<usize>> = HashMap::new();
+        for &i in distinct {
+            let mut key = vec![0; (end - start).div_ceil(64)];
+            for bit in start..end {
+                if labels[i].bit(bit) {
+                    key[(bit - start) / 64] |= 1 << ((bit - start) % 64);
+                }
             }
-            neighbors[pointer].0[j] = (neighbors[pointer].0[j] + 1) % 2;
+            buckets.entry(key).or_default().push(i);
+        }
 
-            pointer += 1;
+        for bucket in buckets.values() {
+            for (k, &i) in bucket.iter().enumerate() {
+                for &j in bucket[..k].iter() {
+                    if !sets.same_set(i, j) && labels[i].distance(&labels[j]) <= d {
+                        sets.union(i, j);
+                    }
+                }
+            }
         }
     }
-    neighbors
 }
 
-fn create_root(node_map: &mut HashMap<usize, Node<usize>>, id: usize) -> Node<usize> {
-    let node = UnionFind::create_root(id);
-    node_map.insert(id, Rc::clone(&node));
-    node
-}
+// Calls f with every label that differs from `label` in 1 to `left` bits,
+// all of them at `start` or above. Leaves `label` as it was.
+fn for_each_neighbour<L, F>(label: &mut L, start: usize, left: usize, f: &mut F)
+where
+    L: HammingLabel,
+    F: FnMut(&L),
+{
+    if left == 0 {
+        return;
+    }
 
-fn fetch_node_by_id(node_map: &mut HashMap<usize, Node<usize>>, id: usize) -> Node<usize> {
-    match node_map.get(&id) {
-        Some(v) => Rc::clone(v),
-        None => panic!("Error map initialization {}", id),
+    for bit in start..label.width() {
+        label.flip(bit);
+        f(label);
+        for_each_neighbour(label, bit + 1, left - 1, f);
+        label.flip(bit);
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::io::prelude::*;
+    use std::collections::HashSet;
 
     #[test]
     fn sould_work_with_small_input() {
@@ -199,6 +781,267 @@ mod tests {
         assert_eq!(result, 106);
     }
 
+    #[test]
+    fn sould_work_with_single_component() {
+        let file = File::open("priv/input.txt").expect("Something went wrong reading the file");
+
+        let reader = BufReader::new(file);
+        let mut lines: Lines<BufReader<File>> = reader.lines();
+        let result = run_clustering_algo_with_edge_input(&mut lines, 1);
+        // The longest edge of the minimum spanning tree.
+        assert_eq!(result, 123);
+    }
+
+    #[test]
+    fn single_link_clusters_works() {
+        let edges = vec![
+            Edge::new(0, 1, 1),
+            Edge::new(1, 2, 2),
+            Edge::new(3, 4, 2),
+            Edge::new(2, 3, 5),
+            Edge::new(0, 2, 3),
+            Edge::new(4, 5, 9),
+            Edge::new(1, 4, 7),
+        ];
+
+        let result = single_link_clusters(&edges, 6, 3);
+        assert_eq!(result.assignments, vec![0, 0, 0, 1, 1, 2]);
+        assert_eq!(result.spacing, Some(5));
+        assert_eq!(
+            result.merges,
+            vec![Edge::new(0, 1, 1), Edge::new(1, 2, 2), Edge::new(3, 4, 2)]
+        );
+        assert_eq!(result.clusters_count(), 3);
+
+        let result = single_link_clusters(&edges, 6, 1);
+        assert_eq!(result.assignments, vec![0; 6]);
+        assert_eq!(result.spacing, None);
+        assert_eq!(result.merges.len(), 5);
+
+        let result = single_link_clusters(&edges, 6, 6);
+        assert_eq!(result.assignments, vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(result.spacing, Some(1));
+        assert!(result.merges.is_empty());
+    }
+
+    #[test]
+    fn single_linkage_works() {
+        let edges = vec![
+            Edge::new(0, 1, 1),
+            Edge::new(1, 2, 2),
+            Edge::new(3, 4, 2),
+            Edge::new(2, 3, 5),
+            Edge::new(0, 2, 3),
+            Edge::new(4, 5, 9),
+            Edge::new(1, 4, 7),
+        ];
+
+        let result = single_linkage(&edges, 6);
+        assert_eq!(
+            result.linkage_matrix(),
+            vec![
+                [0.0, 1.0, 1.0, 2.0],
+                [2.0, 6.0, 2.0, 3.0],
+                [3.0, 4.0, 2.0, 2.0],
+                [7.0, 8.0, 5.0, 5.0],
+                [5.0, 9.0, 9.0, 6.0],
+            ]
+        );
+
+        assert_eq!(result.cut_at_height(0.5), vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(result.cut_at_height(2.0), vec![0, 0, 0, 1, 1, 2]);
+        assert_eq!(result.cut_at_height(8.9), vec![0, 0, 0, 0, 0, 1]);
+        assert_eq!(result.cut_at_k(1), vec![0; 6]);
+        for k in 1..=6 {
+            assert_eq!(
+                result.cut_at_k(k),
+                single_link_clusters(&edges, 6, k).assignments
+            );
+        }
+    }
+
+    #[test]
+    fn single_linkage_should_return_forest() {
+        let edges = vec![Edge::new(0, 1, 4), Edge::new(2, 3, 1)];
+        let result = single_linkage(&edges, 5);
+        assert_eq!(result.merges.len(), 2);
+        assert_eq!(result.merges[1].left, 0);
+        assert_eq!(result.merges[1].right, 1);
+        assert_eq!(result.merges[1].size, 2);
+        assert_eq!(result.cut_at_k(1), vec![0, 0, 1, 1, 2]);
+    }
+
+    fn line_distances(points: &[f64]) -> Vec<Vec<f64>> {
+        points
+            .iter()
+            .map(|a| points.iter().map(|b| (a - b).abs()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn linkage_works() {
+        let distances = line_distances(&[0.0, 1.0, 3.0, 7.0, 8.0]);
+
+        let result = linkage(&distances, Linkage::Complete);
+        assert_eq!(
+            result.linkage_matrix(),
+            vec![
+                [0.0, 1.0, 1.0, 2.0],
+                [3.0, 4.0, 1.0, 2.0],
+                [2.0, 5.0, 3.0, 3.0],
+                [6.0, 7.0, 8.0, 5.0],
+            ]
+        );
+
+        let result = linkage(&distances, Linkage::Average);
+        let heights: Vec<f64> = result.merges.iter().map(|m| m.height).collect();
+        assert_eq!(heights, vec![1.0, 1.0, 2.5, 37.0 / 6.0]);
+        assert_eq!(result.cut_at_k(2), vec![0, 0, 0, 1, 1]);
+
+        // sqrt(2 * |A| * |B| / (|A| + |B|)) * distance between centroids
+        let result = linkage(&distances, Linkage::Ward);
+        let expected = [
+            1.0,
+            1.0,
+            (4.0f64 / 3.0).sqrt() * 2.5,
+            (12.0f64 / 5.0).sqrt() * (7.5 - 4.0 / 3.0),
+        ];
+        for (m, e) in result.merges.iter().zip(expected.iter()) {
+            assert!((m.height - e).abs() < 1e-9);
+        }
+        assert_eq!(result.merges[3].size, 5);
+    }
+
+    #[test]
+    fn single_linkage_should_match_edges() {
+        let n = 30;
+        let distance = |i: usize, j: usize| {
+            let (a, b) = (i.min(j), i.max(j));
+            (a * 97 + b * 31 + a * b * 7) % 1009 + 1
+        };
+
+        let distances: Vec<Vec<f64>> = (0..n)
+            .map(|i| {
+                (0..n)
+                    .map(|j| if i == j { 0.0 } else { distance(i, j) as f64 })
+                    .collect()
+            })
+            .collect();
+        let mut edges = Vec::new();
+        for i in 0..n {
+            for j in i + 1..n {
+                edges.push(Edge::new(i, j, distance(i, j)));
+            }
+        }
+
+        let expected = single_linkage(&edges, n);
+        let result = linkage(&distances, Linkage::Single);
+        assert_eq!(result.merges.len(), n - 1);
+        // Equal distances may merge in another order, cuts at a height may not.
+        for m in expected.merges.iter() {
+            assert_eq!(
+                result.cut_at_height(m.height),
+                expected.cut_at_height(m.height)
+            );
+        }
+
+        for method in [Linkage::Complete, Linkage::Average, Linkage::Ward] {
+            let result = linkage(&distances, method);
+            assert_eq!(result.merges.len(), n - 1);
+            assert_eq!(result.merges[n - 2].size, n);
+            assert!(result.merges.windows(2).all(|w| w[0].height <= w[1].height));
+        }
+    }
+
+    fn blobs() -> Vec<Vec<f64>> {
+        vec![
+            vec![0.0, 0.0],
+            vec![1.0, 0.0],
+            vec![0.0, 1.0],
+            vec![10.0, 10.0],
+            vec![11.0, 10.0],
+            vec![20.0, 0.0],
+        ]
+    }
+
+    #[test]
+    fn metrics_work() {
+        let points = vec![vec![0.0], vec![1.0], vec![4.0], vec![6.0]];
+        let assignments = vec![0, 0, 1, 1];
+
+        // a and b of every point: (1, 5), (1, 4), (2, 3.5), (2, 5.5)
+        let expected = (4.0 / 5.0 + 3.0 / 4.0 + 1.5 / 3.5 + 3.5 / 5.5) / 4.0;
+        let result = silhouette_score(&points, &assignments).unwrap();
+        assert!((result - expected).abs() < 1e-9);
+
+        assert_eq!(dunn_index(&points, &assignments), Some(1.5));
+
+        // Centroids 0.5 and 5, scatters 0.5 and 1.
+        let result = davies_bouldin_index(&points, &assignments).unwrap();
+        assert!((result - 1.5 / 4.5).abs() < 1e-9);
+
+        assert_eq!(silhouette_score(&points, &[0, 0, 0, 0]), None);
+        assert_eq!(dunn_index(&points, &[2, 2, 2, 2]), None);
+        assert_eq!(davies_bouldin_index(&points, &[1, 1, 1, 1]), None);
+    }
+
+    #[test]
+    fn metrics_should_prefer_natural_clusters() {
+        let points = blobs();
+        let good = vec![0, 0, 0, 1, 1, 2];
+        let bad = vec![0, 1, 0, 1, 2, 2];
+
+        assert!(silhouette_score(&points, &good) > silhouette_score(&points, &bad));
+        assert!(dunn_index(&points, &good) > dunn_index(&points, &bad));
+        assert!(davies_bouldin_index(&points, &good) < davies_bouldin_index(&points, &bad));
+    }
+
+    #[test]
+    fn metrics_should_reject_degenerate_clusters() {
+        // Every cluster is a single location.
+        let points = blobs();
+        assert_eq!(dunn_index(&points, &[0, 1, 2, 3, 4, 5]), None);
+
+        // Two clusters share a point.
+        let points = vec![vec![1.0, 1.0], vec![1.0, 1.0], vec![5.0, 5.0]];
+        assert_eq!(dunn_index(&points, &[0, 1, 2]), None);
+
+        // Two clusters share a centroid.
+        let points = vec![
+            vec![0.0, 0.0],
+            vec![2.0, 2.0],
+            vec![0.0, 2.0],
+            vec![2.0, 0.0],
+            vec![9.0, 9.0],
+        ];
+        assert_eq!(davies_bouldin_index(&points, &[0, 0, 1, 1, 2]), None);
+        assert!(davies_bouldin_index(&points, &[0, 1, 0, 1, 2]).is_some());
+    }
+
+    #[test]
+    fn largest_gap_should_pick_k() {
+        let points = blobs();
+        let distances: Vec<Vec<f64>> = points
+            .iter()
+            .map(|a| points.iter().map(|b| euclidean(a, b)).collect())
+            .collect();
+
+        let result = linkage(&distances, Linkage::Single);
+        assert_eq!(result.largest_gap_k(2, 5), Some(3));
+        assert_eq!(result.largest_gap_k(4, 5), Some(4));
+        assert_eq!(result.largest_gap_k(1, 2), Some(2));
+        assert_eq!(result.largest_gap_k(6, 10), None);
+        assert_eq!(result.cut_at_k(3), vec![0, 0, 0, 1, 1, 2]);
+    }
+
+    #[test]
+    fn single_link_clusters_should_stop_on_disconnected_input() {
+        let edges = vec![Edge::new(0, 1, 4), Edge::new(2, 3, 1)];
+        let result = single_link_clusters(&edges, 5, 2);
+        assert_eq!(result.assignments, vec![0, 0, 1, 1, 2]);
+        assert_eq!(result.spacing, None);
+    }
+
     #[test]
     fn sould_work_with_huge_input() {
         let file =
@@ -210,14 +1053,145 @@ mod tests {
         assert_eq!(result, 6118);
     }
 
+    #[test]
+    fn sould_work_with_in_memory_input() {
+        let input = "6 6\n\
+            0 0 0 0 0 0\n\
+            1 1 0 0 0 0\n\
+            0 0 0 1 1 1\n\
+            0 0 1 1 1 1\n\
+            0 0 0 0 0 0\n\
+            1 0 1 0 0 0\n";
+        let result = run_clustering_algo_with_huge_input(&mut input.as_bytes().lines());
+        assert_eq!(result, 2);
+
+        let mut lines = input.as_bytes().lines();
+        lines.next();
+        let labels = init_labels(&mut lines);
+        assert_eq!(labels.len(), 6);
+        assert_eq!(labels[2].width(), 6);
+
+        let expected = hamming_clusters(&labels, 2);
+        assert_eq!(expected.count, result);
+        assert_eq!(expected.assignments, vec![0, 0, 1, 1, 0, 0]);
+    }
+
     #[test]
     fn sould_produce_naighbours() {
-        let origin = [0; 24];
-        let result = produce_neighbours(&origin);
+        let mut origin = BitLabel::new(24);
+        let mut result = Vec::new();
+        for_each_neighbour(&mut origin, 0, 2, &mut |n| result.push(n.clone()));
+
+        assert_eq!(origin, BitLabel::new(24));
+        assert_eq!(result.len(), 300);
+        assert_eq!(result.iter().collect::<HashSet<_>>().len(), 300);
         result.into_iter().for_each(|x| {
-            let sum: u8 = x.0.into_iter().sum();
+            let sum = (0..24).filter(|&i| x.get(i)).count();
             assert!(sum == 1 || sum == 2);
-            assert_eq!(x.1, sum as usize)
         });
     }
+
+    #[test]
+    fn hamming_clusters_works() {
+        let labels: Vec<u64> = vec![
+            0b0000,
+            0b0111,
+            (1 << 63) | (1 << 62),
+            0b0001,
+            0b0111,
+            0b1110_0000,
+            0b0011,
+            (1 << 63) | (1 << 40) | 1,
+        ];
+
+        let result = hamming_clusters(&labels, 1);
+        assert_eq!(result.assignments, vec![0, 0, 1, 0, 0, 2, 0, 3]);
+        assert_eq!(result.count, 4);
+
+        let result = hamming_clusters(&labels, 0);
+        assert_eq!(result.count, 7);
+        assert_eq!(result.assignments[1], result.assignments[4]);
+
+        let result = hamming_clusters(&labels, 3);
+        assert_eq!(result.count, 1);
+    }
+
+    fn pairwise_sets<L: HammingLabel>(labels: &[L], d: usize) -> DisjointSet {
+        let mut sets = DisjointSet::new(labels.len());
+        for i in 0..labels.len() {
+            for j in 0..i {
+                if labels[i].distance(&labels[j]) <= d {
+                    sets.union(i, j);
+                }
+            }
+        }
+        sets
+    }
+
+    #[test]
+    fn hamming_searches_should_agree() {
+        let labels: Vec<u64> = (0..300u64)
+            .map(|i| (i * 0x9e37_79b9_7f4a_7c15) & 0xf0f0_0000_0000_0fff)
+            .collect();
+        let first_seen: HashMap<&u64, usize> = labels.iter().zip(0..).collect();
+        let distinct: Vec<usize> = first_seen.values().copied().collect();
+
+        for d in 0..=3 {
+            let mut expected = pairwise_sets(&labels, d);
+
+            let mut sets = DisjointSet::new(labels.len());
+            link_neighbours(&first_seen, d, &mut sets);
+            assert_eq!(assignments(&mut sets), assignments(&mut expected));
+
+            let mut sets = DisjointSet::new(labels.len());
+            link_by_blocks(&labels, &distinct, d, &mut sets);
+            assert_eq!(assignments(&mut sets), assignments(&mut expected));
+        }
+    }
+
+    #[test]
+    fn hamming_clusters_should_handle_wide_labels() {
+        // Random 128-bit labels, some with a few bits flipped.
+        let mut state: u128 = 0x2545_f491_4f6c_dd1d;
+        let mut labels = Vec::new();
+        for i in 0..4000 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            labels.push(if i % 3 == 0 && i > 0 {
+                labels[i - 1] ^ (state & 0b111) ^ (1 << (state % 128))
+            } else {
+                state
+            });
+        }
+
+        let result = hamming_clusters(&labels, 3);
+        let mut expected = pairwise_sets(&labels, 3);
+        assert_eq!(result.count, expected.count_sets());
+        assert_eq!(result.assignments, assignments(&mut expected));
+        assert!(result.count < labels.len());
+    }
+
+    #[test]
+    fn hamming_clusters_should_match_pairwise() {
+        let labels: Vec<u128> = (0..24u128)
+            .map(|i| (i * 0x9e37_79b9_7f4a_7c15) ^ (i << 100))
+            .map(|x| x & (((1 << 12) - 1) | (0b111 << 120)))
+            .collect();
+        let bits: Vec<BitLabel> = labels
+            .iter()
+            .map(|&x| BitLabel::from_bits(&(0..128).map(|i| x >> i & 1 == 1).collect::<Vec<_>>()))
+            .collect();
+
+        for d in 0..=3 {
+            let mut sets = pairwise_sets(&labels, d);
+
+            let result = hamming_clusters(&labels, d);
+            assert_eq!(result.assignments, assignments(&mut sets));
+            assert_eq!(result.count, sets.count_sets());
+            if d < 3 {
+                assert_eq!(hamming_clusters(&bits, d), result);
+            }
+        }
+    }
 }
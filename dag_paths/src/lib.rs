@@ -0,0 +1,197 @@
+use std::collections::HashSet;
+
+use graph_representations::{AdjacencyListGraphOutcome, SimpleGraph};
+use topological_sort::Grpah;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct DagPaths {
+    pub distances: Vec<Option<isize>>,
+    pub predecessors: Vec<Option<usize>>,
+}
+
+impl DagPaths {
+    // Vertices from the start of the path to target, None if target is unreachable.
+    pub fn path_to(&self, target: usize) -> Option<Vec<usize>> {
+        self.distances[target]?;
+
+        let mut path = vec![target];
+        let mut current = target;
+        while let Some(prev) = self.predecessors[current] {
+            path.push(prev);
+            current = prev;
+        }
+        path.reverse();
+
+        Some(path)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct CriticalPath {
+    pub length: isize,
+    pub path: Vec<usize>,
+}
+
+// Topological order of a directed graph, None if it has a cycle. An
+// undirected graph with at least one edge is never acyclic here.
+pub fn topological_order(graph: &SimpleGraph) -> Option<Vec<usize>> {
+    let mut edges_set = HashSet::with_capacity(graph.edges.len());
+    for e in graph.edges.iter() {
+        edges_set.insert((e.from, e.to));
+        if !graph.is_directed {
+            edges_set.insert((e.to, e.from));
+        }
+    }
+
+    Grpah::new(graph.size, &edges_set).order()
+}
+
+// O(n + m), negative lengths are fine. None if the graph has a cycle.
+pub fn dag_shortest_paths(graph: &SimpleGraph, source: usize) -> Option<DagPaths> {
+    relax_in_order(graph, &[source], |new, old| new < old)
+}
+
+// O(n + m). None if the graph has a cycle.
+pub fn dag_longest_paths(graph: &SimpleGraph, source: usize) -> Option<DagPaths> {
+    relax_in_order(graph, &[source], |new, old| new > old)
+}
+
+// Longest path of the whole graph, every vertex may start it. With tasks as
+// vertices and durations on outgoing edges it's the project critical path.
+pub fn critical_path(graph: &SimpleGraph) -> Option<CriticalPath> {
+    let sources: Vec<usize> = (0..graph.size).collect();
+    let paths = relax_in_order(graph, &sources, |new, old| new > old)?;
+
+    let mut end = None;
+    let mut length = 0;
+    for (i, d) in paths.distances.iter().enumerate() {
+        if let Some(d) = *d {
+            if end.is_none() || d > length {
+                end = Some(i);
+                length = d;
+            }
+        }
+    }
+
+    let path = match end {
+        Some(i) => paths.path_to(i)?,
+        None => Vec::new(),
+    };
+
+    Some(CriticalPath { length, path })
+}
+
+fn relax_in_order<F>(graph: &SimpleGraph, sources: &[usize], is_better: F) -> Option<DagPaths>
+where
+    F: Fn(isize, isize) -> bool,
+{
+    let order = topological_order(graph)?;
+    let outcome_graph = AdjacencyListGraphOutcome::from_simple(graph);
+
+    let mut distances = vec![None; graph.size];
+    let mut predecessors = vec![None; graph.size];
+    for &s in sources {
+        distances[s] = Some(0);
+    }
+
+    for from in order {
+        let from_distance = match distances[from] {
+            Some(v) => v,
+            None => continue,
+        };
+
+        for e in outcome_graph.edges[from].iter() {
+            let new_distance = from_distance + e.length;
+            let to = e.from_or_to;
+            if distances[to].is_none_or(|old| is_better(new_distance, old)) {
+                distances[to] = Some(new_distance);
+                predecessors[to] = Some(from);
+            }
+        }
+    }
+
+    Some(DagPaths {
+        distances,
+        predecessors,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use graph_representations::Edge;
+
+    use super::*;
+
+    fn build_graph() -> SimpleGraph {
+        let edges = vec![
+            Edge::new(0, 1, 5),
+            Edge::new(0, 2, 3),
+            Edge::new(1, 3, 6),
+            Edge::new(1, 2, 2),
+            Edge::new(2, 4, 4),
+            Edge::new(2, 5, 2),
+            Edge::new(2, 3, 7),
+            Edge::new(3, 5, 1),
+            Edge::new(3, 4, -1),
+            Edge::new(4, 5, -2),
+        ];
+        SimpleGraph::new(7, edges, true)
+    }
+
+    #[test]
+    fn shortest_paths_work() {
+        let graph = build_graph();
+        let result = dag_shortest_paths(&graph, 1).unwrap();
+        assert_eq!(
+            result.distances,
+            vec![None, Some(0), Some(2), Some(6), Some(5), Some(3), None]
+        );
+        assert_eq!(result.path_to(4), Some(vec![1, 3, 4]));
+        assert_eq!(result.path_to(5), Some(vec![1, 3, 4, 5]));
+        assert_eq!(result.path_to(0), None);
+    }
+
+    #[test]
+    fn longest_paths_work() {
+        let graph = build_graph();
+        let result = dag_longest_paths(&graph, 1).unwrap();
+        assert_eq!(
+            result.distances,
+            vec![None, Some(0), Some(2), Some(9), Some(8), Some(10), None]
+        );
+        assert_eq!(result.path_to(5), Some(vec![1, 2, 3, 5]));
+    }
+
+    #[test]
+    fn critical_path_works() {
+        let graph = build_graph();
+        let result = critical_path(&graph).unwrap();
+        assert_eq!(
+            result,
+            CriticalPath {
+                length: 15,
+                path: vec![0, 1, 2, 3, 5],
+            }
+        );
+    }
+
+    #[test]
+    fn should_reject_cycles() {
+        let mut graph = build_graph();
+        graph.add_edge(Edge::new(5, 0, 1));
+        assert_eq!(topological_order(&graph), None);
+        assert_eq!(dag_shortest_paths(&graph, 0), None);
+        assert_eq!(critical_path(&graph), None);
+    }
+
+    #[test]
+    fn should_handle_long_chain() {
+        let size = 200_000;
+        let edges = (1..size).map(|i| Edge::new(i - 1, i, 2)).collect();
+        let graph = SimpleGraph::new(size, edges, true);
+
+        let result = critical_path(&graph).unwrap();
+        assert_eq!(result.length, 2 * (size as isize - 1));
+        assert_eq!(result.path.len(), size);
+    }
+}
@@ -8,13 +8,24 @@ pub fn dijkstra(graph: &AdjacencyListGraphOutcome, source: usize) -> Vec<isize>
 pub fn dijkstra_with_reweighting(
     graph: &AdjacencyListGraphOutcome,
     source: usize,
-    reweight_vec: &Vec<isize>,
+    reweight_vec: &[isize],
 ) -> Vec<isize> {
+    dijkstra_tree_with_reweighting(graph, source, reweight_vec).0
+}
+
+// Same as dijkstra_with_reweighting but also returns the parent of every
+// reached vertex in the shortest path tree. The source has no parent.
+pub fn dijkstra_tree_with_reweighting(
+    graph: &AdjacencyListGraphOutcome,
+    source: usize,
+    reweight_vec: &[isize],
+) -> (Vec<isize>, Vec<Option<usize>>) {
     let mut vertex_heap: UpdateableHeap<(usize, usize, isize)> = UpdateableHeap::new(graph.size);
     vertex_heap.fill();
     vertex_heap.decrease_by_idx(source, 0, (source, source, 0));
     let mut scores = vec![isize::MAX; graph.size];
     let mut real_scores = vec![isize::MAX; graph.size];
+    let mut parents = vec![None; graph.size];
     real_scores[source] = 0;
     scores[source] = 0;
 
@@ -43,6 +54,9 @@ pub fn dijkstra_with_reweighting(
                 + prev_len
                 + reweight_edge(reweight_vec, from_vertex, active_vertex);
         }
+        if active_vertex != source {
+            parents[active_vertex] = Some(from_vertex);
+        }
         let edges = &graph.edges[active_vertex];
         for edge in edges.iter() {
             vertex_heap.decrease_by_idx(
@@ -53,7 +67,7 @@ pub fn dijkstra_with_reweighting(
         }
     }
 
-    real_scores
+    (real_scores, parents)
 }
 
 fn shortest_path(graph: &AdjacencyListGraphOutcome, src: usize) -> Vec<isize> {
@@ -69,6 +83,10 @@ fn shortest_path(graph: &AdjacencyListGraphOutcome, src: usize) -> Vec<isize> {
         let active_vertex = elem.idx;
         let active_scores = elem.ordering_key;
 
+        if active_scores == isize::MAX {
+            break;
+        }
+
         if active_scores < max_scores {
             panic!("not correct algo {} {}", max_scores, active_scores);
         }
@@ -89,7 +107,7 @@ fn shortest_path(graph: &AdjacencyListGraphOutcome, src: usize) -> Vec<isize> {
     scores
 }
 
-fn reweight_edge(reweight_vec: &Vec<isize>, from: usize, to: usize) -> isize {
+fn reweight_edge(reweight_vec: &[isize], from: usize, to: usize) -> isize {
     reweight_vec[to] - reweight_vec[from]
 }
 
@@ -298,4 +316,41 @@ mod tests {
         let result = dijkstra_with_reweighting(&outcome, 0, &v);
         assert_eq!(result, vec![0, -2, -3, -6, isize::MAX, -1]);
     }
+
+    #[test]
+    fn tree_should_have_parents() {
+        let edges: Edges = vec![
+            Edge::new(0, 1, 0),
+            Edge::new(1, 2, 0),
+            Edge::new(2, 0, 1),
+            Edge::new(2, 3, 0),
+            Edge::new(4, 3, 2),
+            Edge::new(4, 5, 2),
+            Edge::new(2, 5, 0),
+        ];
+
+        let graph = SimpleGraph::new(6, edges, true);
+        let v = vec![0, -2, -3, -6, 0, -1, 0];
+        let outcome = AdjacencyListGraphOutcome::from_simple(&graph);
+        let (scores, parents) = dijkstra_tree_with_reweighting(&outcome, 0, &v);
+        assert_eq!(scores, vec![0, -2, -3, -6, isize::MAX, -1]);
+        assert_eq!(
+            parents,
+            vec![None, Some(0), Some(1), Some(2), None, Some(2)]
+        );
+    }
+
+    #[test]
+    fn should_skip_unreachable_edges() {
+        let edges: Edges = vec![
+            Edge::new(1, 2, 5),
+            Edge::new(2, 3, 5),
+            Edge::new(3, 1, 5),
+            Edge::new(0, 4, 1),
+        ];
+        let graph = SimpleGraph::new(5, edges, true);
+        let outcome = AdjacencyListGraphOutcome::from_simple(&graph);
+        let result = dijkstra(&outcome, 0);
+        assert_eq!(result, vec![0, isize::MAX, isize::MAX, isize::MAX, 1]);
+    }
 }
@@ -0,0 +1,247 @@
+use graph_representations::{DistanceMatrix, SimpleGraph};
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+// O(n^3). None when the graph has a negative cycle: a vertex on such a cycle
+// gets a negative distance to itself, so the diagonal is checked after every
+// row update and the run stops as soon as one shows up.
+pub fn floyd_warshall(graph: &SimpleGraph) -> Option<DistanceMatrix> {
+    let size = graph.size;
+    let mut distances = vec![isize::MAX; size * size];
+    let mut next_hops = vec![DistanceMatrix::NO_HOP; size * size];
+
+    for i in 0..size {
+        distances[i * size + i] = 0;
+    }
+
+    for e in graph.edges.iter() {
+        relax_edge(&mut distances, &mut next_hops, size, e.from, e.to, e.length);
+        if !graph.is_directed {
+            relax_edge(&mut distances, &mut next_hops, size, e.to, e.from, e.length);
+        }
+    }
+
+    for k in 0..size {
+        for i in 0..size {
+            let to_k = distances[i * size + k];
+            if to_k == isize::MAX {
+                continue;
+            }
+            let hop_to_k = next_hops[i * size + k];
+
+            for j in 0..size {
+                let from_k = distances[k * size + j];
+                if from_k == isize::MAX {
+                    continue;
+                }
+
+                let idx = i * size + j;
+                if to_k + from_k < distances[idx] {
+                    distances[idx] = to_k + from_k;
+                    next_hops[idx] = hop_to_k;
+                }
+            }
+
+            if distances[i * size + i] < 0 {
+                return None;
+            }
+        }
+    }
+
+    let mut matrix = DistanceMatrix::new(size);
+    for i in 0..size {
+        for j in 0..size {
+            let idx = i * size + j;
+            if i != j && distances[idx] != isize::MAX {
+                matrix.set(i, j, Some(distances[idx]), Some(next_hops[idx]));
+            }
+        }
+    }
+
+    Some(matrix)
+}
+
+fn relax_edge(
+    distances: &mut [isize],
+    next_hops: &mut [usize],
+    size: usize,
+    from: usize,
+    to: usize,
+    length: isize,
+) {
+    let idx = from * size + to;
+    if length < distances[idx] {
+        distances[idx] = length;
+        next_hops[idx] = to;
+    }
+}
+
+// Reachability over at least one edge stored as one bitset row per vertex, so
+// a vertex reaches itself only when it lies on a cycle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransitiveClosure {
+    size: usize,
+    words: usize,
+    bits: Vec<u64>,
+}
+
+impl TransitiveClosure {
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn is_reachable(&self, from: usize, to: usize) -> bool {
+        self.bits[from * self.words + to / WORD_BITS] & (1 << (to % WORD_BITS)) != 0
+    }
+
+    pub fn reachable(&self, from: usize) -> impl Iterator<Item = usize> + '_ {
+        (0..self.size).filter(move |&to| self.is_reachable(from, to))
+    }
+
+    fn set(&mut self, from: usize, to: usize) {
+        self.bits[from * self.words + to / WORD_BITS] |= 1 << (to % WORD_BITS);
+    }
+
+    fn union_rows(&mut self, target: usize, source: usize) {
+        for w in 0..self.words {
+            let word = self.bits[source * self.words + w];
+            self.bits[target * self.words + w] |= word;
+        }
+    }
+}
+
+// Warshall's algorithm, O(n^3 / 64). Edge lengths are ignored.
+pub fn transitive_closure(graph: &SimpleGraph) -> TransitiveClosure {
+    let size = graph.size;
+    let words = size.div_ceil(WORD_BITS);
+    let mut closure = TransitiveClosure {
+        size,
+        words,
+        bits: vec![0; size * words],
+    };
+
+    for e in graph.edges.iter() {
+        closure.set(e.from, e.to);
+        if !graph.is_directed {
+            closure.set(e.to, e.from);
+        }
+    }
+
+    for k in 0..size {
+        for i in 0..size {
+            if closure.is_reachable(i, k) {
+                closure.union_rows(i, k);
+            }
+        }
+    }
+
+    closure
+}
+
+#[cfg(test)]
+mod tests {
+    use graph_representations::Edge;
+
+    use super::*;
+
+    #[test]
+    fn it_works() {
+        let edges = vec![
+            Edge::new(0, 1, -2),
+            Edge::new(1, 2, -1),
+            Edge::new(2, 0, 4),
+            Edge::new(2, 3, -3),
+            Edge::new(4, 3, -4),
+            Edge::new(4, 5, 1),
+            Edge::new(2, 5, 2),
+        ];
+
+        let graph = SimpleGraph::new(6, edges, true);
+        let result = floyd_warshall(&graph).unwrap();
+        assert_eq!(
+            result.row(0).collect::<Vec<_>>(),
+            vec![Some(0), Some(-2), Some(-3), Some(-6), None, Some(-1)]
+        );
+        assert_eq!(
+            result.column(3).collect::<Vec<_>>(),
+            vec![Some(-6), Some(-4), Some(-3), Some(0), Some(-4), None]
+        );
+        assert_eq!(result.path(0, 5), Some(vec![0, 1, 2, 5]));
+        assert_eq!(result.path(4, 4), Some(vec![4]));
+        assert_eq!(result.path(5, 0), None);
+    }
+
+    #[test]
+    fn it_works_undirected() {
+        let edges = vec![Edge::new(0, 1, 5), Edge::new(1, 2, 6), Edge::new(0, 2, 15)];
+
+        let graph = SimpleGraph::new(3, edges, false);
+        let result = floyd_warshall(&graph).unwrap();
+        assert_eq!(result.get(2, 0), Some(11));
+        assert_eq!(result.path(2, 0), Some(vec![2, 1, 0]));
+    }
+
+    #[test]
+    fn should_detect_negative_cycle() {
+        let edges = vec![Edge::new(0, 1, 1), Edge::new(1, 2, -3), Edge::new(2, 0, 1)];
+
+        let graph = SimpleGraph::new(3, edges, true);
+        assert_eq!(floyd_warshall(&graph), None);
+    }
+
+    #[test]
+    fn should_detect_negative_self_loop() {
+        let edges = vec![Edge::new(0, 1, 1), Edge::new(2, 2, -1)];
+
+        let graph = SimpleGraph::new(3, edges, true);
+        assert_eq!(floyd_warshall(&graph), None);
+    }
+
+    #[test]
+    fn closure_works() {
+        let edges = vec![
+            Edge::new(0, 1, 1),
+            Edge::new(1, 2, 1),
+            Edge::new(2, 1, 1),
+            Edge::new(3, 0, 1),
+            Edge::new(69, 70, 1),
+            Edge::new(70, 3, 1),
+        ];
+
+        let graph = SimpleGraph::new(72, edges, true);
+        let result = transitive_closure(&graph);
+        assert_eq!(result.reachable(0).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(result.reachable(1).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(
+            result.reachable(69).collect::<Vec<_>>(),
+            vec![0, 1, 2, 3, 70]
+        );
+        assert!(!result.is_reachable(0, 0));
+        assert!(!result.is_reachable(2, 69));
+        assert_eq!(result.reachable(71).count(), 0);
+    }
+
+    #[test]
+    fn closure_should_match_distances() {
+        let edges = vec![
+            Edge::new(0, 1, -2),
+            Edge::new(1, 2, -1),
+            Edge::new(2, 0, 4),
+            Edge::new(2, 3, -3),
+            Edge::new(4, 3, -4),
+            Edge::new(4, 5, 1),
+            Edge::new(2, 5, 2),
+        ];
+
+        let graph = SimpleGraph::new(6, edges, true);
+        let closure = transitive_closure(&graph);
+        let distances = floyd_warshall(&graph).unwrap();
+        for i in 0..6 {
+            for j in 0..6 {
+                if i != j {
+                    assert_eq!(closure.is_reachable(i, j), distances.get(i, j).is_some());
+                }
+            }
+        }
+    }
+}
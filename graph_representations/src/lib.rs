@@ -75,6 +75,53 @@ impl SimpleGraph {
     }
 }
 
+// Edge carrying several weights, e.g. a cost and the resources it consumes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiWeightEdge {
+    pub from: usize,
+    pub to: usize,
+    pub weights: Vec<isize>,
+}
+
+impl MultiWeightEdge {
+    pub fn new(from: usize, to: usize, weights: Vec<isize>) -> Self {
+        MultiWeightEdge { from, to, weights }
+    }
+}
+
+#[derive(Debug)]
+pub struct MultiWeightGraph {
+    pub size: usize,
+    pub is_directed: bool,
+    pub edges: Vec<MultiWeightEdge>,
+}
+
+impl MultiWeightGraph {
+    pub fn new(size: usize, edges: Vec<MultiWeightEdge>, is_directed: bool) -> Self {
+        MultiWeightGraph {
+            size,
+            edges,
+            is_directed,
+        }
+    }
+
+    pub fn add_edge(&mut self, edge: MultiWeightEdge) {
+        self.edges.push(edge);
+    }
+
+    // Indices of edges leaving every vertex, both ends for undirected graphs.
+    pub fn outcome_edges(&self) -> Vec<Vec<usize>> {
+        let mut result = vec![Vec::new(); self.size];
+        for (idx, e) in self.edges.iter().enumerate() {
+            result[e.from].push(idx);
+            if !self.is_directed {
+                result[e.to].push(idx);
+            }
+        }
+        result
+    }
+}
+
 #[derive(Debug, Clone, Copy, Eq)]
 pub struct AdjacencyListEdge {
     pub length: isize,
@@ -230,3 +277,87 @@ impl AdjacencyListGraphOutcome {
         }
     }
 }
+
+// Dense all-pairs result stored row by row in a single allocation. next_hops
+// holds the vertex that follows `from` on a shortest path to `to`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DistanceMatrix {
+    size: usize,
+    distances: Vec<isize>,
+    next_hops: Vec<usize>,
+}
+
+impl DistanceMatrix {
+    // Stored in next_hops for pairs without a path.
+    pub const NO_HOP: usize = usize::MAX;
+
+    // Every pair is unreachable except a vertex and itself.
+    pub fn new(size: usize) -> Self {
+        let mut distances = vec![isize::MAX; size * size];
+        for i in 0..size {
+            distances[i * size + i] = 0;
+        }
+
+        DistanceMatrix {
+            size,
+            distances,
+            next_hops: vec![Self::NO_HOP; size * size],
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn get(&self, from: usize, to: usize) -> Option<isize> {
+        let distance = self.distances[from * self.size + to];
+        if distance == isize::MAX {
+            None
+        } else {
+            Some(distance)
+        }
+    }
+
+    pub fn next_hop(&self, from: usize, to: usize) -> Option<usize> {
+        let hop = self.next_hops[from * self.size + to];
+        if hop == Self::NO_HOP {
+            None
+        } else {
+            Some(hop)
+        }
+    }
+
+    pub fn set(
+        &mut self,
+        from: usize,
+        to: usize,
+        distance: Option<isize>,
+        next_hop: Option<usize>,
+    ) {
+        let idx = from * self.size + to;
+        self.distances[idx] = distance.unwrap_or(isize::MAX);
+        self.next_hops[idx] = next_hop.unwrap_or(Self::NO_HOP);
+    }
+
+    // Vertices of a shortest path including both ends.
+    pub fn path(&self, from: usize, to: usize) -> Option<Vec<usize>> {
+        self.get(from, to)?;
+
+        let mut path = vec![from];
+        let mut current = from;
+        while current != to {
+            current = self.next_hop(current, to)?;
+            path.push(current);
+        }
+
+        Some(path)
+    }
+
+    pub fn row(&self, from: usize) -> impl Iterator<Item = Option<isize>> + '_ {
+        (0..self.size).map(move |to| self.get(from, to))
+    }
+
+    pub fn column(&self, to: usize) -> impl Iterator<Item = Option<isize>> + '_ {
+        (0..self.size).map(move |from| self.get(from, to))
+    }
+}
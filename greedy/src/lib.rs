@@ -1,65 +1,94 @@
-use std::cmp::Ordering;
-use std::collections::{BinaryHeap, HashSet};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, LinkedList};
 
 use std::fs::File;
 
 use std::io::{BufReader, Lines};
 
-#[derive(Eq)]
-struct Task {
-    pub len: isize,
+use graph_representations::{AdjacencyListGraphOutcome, Edge, Edges, SimpleGraph};
+use std::thread;
+use union_find::DisjointSet;
+use updatable_heap::UpdateableHeap;
+
+pub mod multi_machine;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Task {
     pub weight: isize,
+    pub length: isize,
+    pub deadline: Option<isize>,
+    pub release: Option<isize>,
 }
 
+#[derive(Eq)]
+struct DiffTask(Task);
+
 #[derive(Eq)]
 struct OptimalTaks(Task);
 
 impl Task {
-    pub fn new(weight: isize, len: isize) -> Self {
-        Task { len, weight }
+    pub fn new(weight: isize, length: isize) -> Self {
+        Task {
+            weight,
+            length,
+            deadline: None,
+            release: None,
+        }
+    }
+
+    pub fn with_deadline(mut self, deadline: isize) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    pub fn with_release(mut self, release: isize) -> Self {
+        self.release = Some(release);
+        self
+    }
+
+    fn get_deadline(&self, idx: usize) -> Result<isize, ScheduleError> {
+        self.deadline.ok_or(ScheduleError::MissingDeadline(idx))
+    }
+
+    // As an interval the task occupies [release, release + length).
+    fn interval(&self, idx: usize) -> Result<(isize, isize), ScheduleError> {
+        let start = self.release.ok_or(ScheduleError::MissingRelease(idx))?;
+        Ok((start, start + self.length))
     }
+}
 
+impl DiffTask {
     fn diff(&self) -> isize {
-        self.weight - self.len
+        self.0.weight - self.0.length
     }
 }
 
-impl PartialEq for Task {
+impl PartialEq for DiffTask {
     fn eq(&self, other: &Self) -> bool {
-        self.diff() == other.diff() && self.weight == other.weight
+        self.cmp(other) == Ordering::Equal
     }
 }
 
-impl PartialOrd for Task {
+impl PartialOrd for DiffTask {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
     }
 }
 
-impl Ord for Task {
+impl Ord for DiffTask {
     fn cmp(&self, other: &Self) -> Ordering {
         let res = self.diff().cmp(&other.diff());
         if res == Ordering::Equal {
-            self.weight.cmp(&other.weight)
+            self.0.weight.cmp(&other.0.weight)
         } else {
             res
         }
     }
 }
 
-impl OptimalTaks {
-    pub fn new(weight: isize, len: isize) -> Self {
-        OptimalTaks(Task::new(weight, len))
-    }
-
-    fn diff(&self) -> f32 {
-        self.0.weight as f32 / self.0.len as f32
-    }
-}
-
 impl PartialEq for OptimalTaks {
     fn eq(&self, other: &Self) -> bool {
-        self.diff() == other.diff()
+        self.cmp(other) == Ordering::Equal
     }
 }
 
@@ -69,102 +98,459 @@ impl PartialOrd for OptimalTaks {
     }
 }
 
+// Compares weight / length exactly: w1 / l1 vs w2 / l2 is w1 * l2 vs w2 * l1
+// in i128, flipped when the lengths have different signs. Lengths must not
+// be 0. Heavier first on equal ratios, like DiffTask.
 impl Ord for OptimalTaks {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.diff().partial_cmp(&other.diff()).unwrap()
+        let left = self.0.weight as i128 * other.0.length as i128;
+        let right = other.0.weight as i128 * self.0.length as i128;
+        let res = if (self.0.length < 0) == (other.0.length < 0) {
+            left.cmp(&right)
+        } else {
+            right.cmp(&left)
+        };
+
+        res.then(self.0.weight.cmp(&other.0.weight))
     }
 }
 
-pub fn schedule(lines: &mut Lines<BufReader<File>>) -> isize {
-    let count = lines.next().unwrap().unwrap().parse().unwrap();
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduleRule {
+    // Greatest weight - length first, heavier first on ties. Not optimal.
+    Difference,
+    // Greatest weight / length first. Minimises the weighted completion sum.
+    Ratio,
+}
 
-    let mut heap: BinaryHeap<Task> = BinaryHeap::with_capacity(count);
+#[derive(Debug, PartialEq, Eq)]
+pub struct Schedule {
+    // Task indices in execution order.
+    pub order: Vec<usize>,
+    // Completion time of every task, indexed like the input.
+    pub completion_times: Vec<isize>,
+    // Sum of weight * completion time.
+    pub objective: isize,
+}
 
-    for line_res in lines {
-        if let Ok(line) = line_res {
-            let raw_data: Vec<&str> = line.split(" ").collect();
-            let task = Task::new(raw_data[0].parse().unwrap(), raw_data[1].parse().unwrap());
-            heap.push(task)
+// O(n log n). Tasks the rule can't tell apart keep their input order. The
+// ratio rule rejects tasks of length 0.
+pub fn schedule_tasks(tasks: &[Task], rule: ScheduleRule) -> Result<Schedule, ScheduleError> {
+    if rule == ScheduleRule::Ratio {
+        if let Some(i) = tasks.iter().position(|t| t.length == 0) {
+            return Err(ScheduleError::ZeroLength(i));
+        }
+    }
+
+    let mut order: Vec<usize> = (0..tasks.len()).collect();
+    match rule {
+        ScheduleRule::Difference => {
+            order.sort_by(|&a, &b| DiffTask(tasks[b]).cmp(&DiffTask(tasks[a])))
+        }
+        ScheduleRule::Ratio => {
+            order.sort_by(|&a, &b| OptimalTaks(tasks[b]).cmp(&OptimalTaks(tasks[a])))
         }
     }
 
-    let mut len = 0;
-    let mut sum = 0;
+    let mut completion_times = vec![0; tasks.len()];
+    let mut time = 0;
+    let mut objective = 0;
 
-    while let Some(i) = heap.pop() {
-        len += i.len;
-        sum += len * i.weight;
+    for &i in order.iter() {
+        time += tasks[i].length;
+        completion_times[i] = time;
+        objective += time * tasks[i].weight;
     }
 
-    sum
+    Ok(Schedule {
+        order,
+        completion_times,
+        objective,
+    })
 }
 
-pub fn schedule_optiomal(lines: &mut Lines<BufReader<File>>) -> isize {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduleError {
+    // Index of the task without a deadline.
+    MissingDeadline(usize),
+    // Index of the task without a release time.
+    MissingRelease(usize),
+    // Index of a task with a release time the scheduler can't honour.
+    UnsupportedRelease(usize),
+    // Index of the task with length 0, its weight / length ratio is undefined.
+    ZeroLength(usize),
+    // The precedence constraints have a cycle.
+    Cycle,
+    // A precedence constraint names this index, but there is no such task.
+    UnknownTask(usize),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct IntervalSelection {
+    // Chosen task indices ordered by start.
+    pub tasks: Vec<usize>,
+    pub weight: isize,
+}
+
+fn order_by_deadline(tasks: &[Task]) -> Result<Vec<usize>, ScheduleError> {
+    let deadlines = tasks
+        .iter()
+        .enumerate()
+        .map(|(i, t)| t.get_deadline(i))
+        .collect::<Result<Vec<isize>, ScheduleError>>()?;
+
+    let mut order: Vec<usize> = (0..tasks.len()).collect();
+    order.sort_by_key(|&i| deadlines[i]);
+    Ok(order)
+}
+
+// Earliest deadline first, O(n log n). Starting at time 0, whenever the
+// machine is free it runs the released task with the earliest deadline,
+// idling until the next release if none is ready; tasks without a release
+// are ready from the start. The objective of the result is the maximum
+// lateness, completion time - deadline, so it's negative when every task is
+// early. Minimal when all tasks are ready at once; with release times the
+// problem is NP-hard and this is a heuristic.
+pub fn earliest_deadline_first(tasks: &[Task]) -> Result<Schedule, ScheduleError> {
+    let deadlines = tasks
+        .iter()
+        .enumerate()
+        .map(|(i, t)| t.get_deadline(i))
+        .collect::<Result<Vec<isize>, ScheduleError>>()?;
+    let release = |i: usize| tasks[i].release.unwrap_or(0);
+
+    let mut by_release: Vec<usize> = (0..tasks.len()).collect();
+    by_release.sort_by_key(|&i| release(i));
+
+    let mut ready = BinaryHeap::new();
+    let mut next = 0;
+    let mut order = Vec::with_capacity(tasks.len());
+    let mut completion_times = vec![0; tasks.len()];
+    let mut time = 0;
+    let mut objective = isize::MIN;
+
+    while order.len() < tasks.len() {
+        if ready.is_empty() {
+            time = time.max(release(by_release[next]));
+        }
+        while next < tasks.len() && release(by_release[next]) <= time {
+            let i = by_release[next];
+            ready.push(Reverse((deadlines[i], i)));
+            next += 1;
+        }
+
+        let Reverse((deadline, i)) = ready.pop().unwrap();
+        time += tasks[i].length;
+        completion_times[i] = time;
+        objective = objective.max(time - deadline);
+        order.push(i);
+    }
+
+    Ok(Schedule {
+        order,
+        completion_times,
+        objective: if tasks.is_empty() { 0 } else { objective },
+    })
+}
+
+// Moore-Hodgson, O(n log n). Minimises the number of late tasks, the
+// objective of the result. On-time tasks run first in deadline order, the
+// dropped ones after them in input order. All tasks must be ready at time 0,
+// so a task with a release time is rejected.
+pub fn moore_hodgson(tasks: &[Task]) -> Result<Schedule, ScheduleError> {
+    if let Some(i) = tasks.iter().position(|t| t.release.is_some()) {
+        return Err(ScheduleError::UnsupportedRelease(i));
+    }
+
+    let mut on_time = BinaryHeap::new();
+    let mut time = 0;
+
+    for i in order_by_deadline(tasks)? {
+        time += tasks[i].length;
+        on_time.push((tasks[i].length, i));
+
+        if time > tasks[i].deadline.unwrap() {
+            let (length, _) = on_time.pop().unwrap();
+            time -= length;
+        }
+    }
+
+    let mut is_late = vec![true; tasks.len()];
+    for &(_, i) in on_time.iter() {
+        is_late[i] = false;
+    }
+
+    let mut order: Vec<usize> = on_time.into_iter().map(|(_, i)| i).collect();
+    order.sort_by_key(|&i| (tasks[i].deadline, i));
+    let objective = (0..tasks.len()).filter(|&i| is_late[i]).count() as isize;
+    order.extend((0..tasks.len()).filter(|&i| is_late[i]));
+
+    let mut completion_times = vec![0; tasks.len()];
+    let mut time = 0;
+    for &i in order.iter() {
+        time += tasks[i].length;
+        completion_times[i] = time;
+    }
+
+    Ok(Schedule {
+        order,
+        completion_times,
+        objective,
+    })
+}
+
+fn intervals(tasks: &[Task]) -> Result<Vec<(isize, isize)>, ScheduleError> {
+    tasks
+        .iter()
+        .enumerate()
+        .map(|(i, t)| t.interval(i))
+        .collect()
+}
+
+// Earliest finish first, O(n log n). Picks the most tasks whose intervals
+// don't overlap, weights are only summed up. Touching intervals don't overlap.
+pub fn interval_scheduling(tasks: &[Task]) -> Result<IntervalSelection, ScheduleError> {
+    let intervals = intervals(tasks)?;
+    let mut order: Vec<usize> = (0..tasks.len()).collect();
+    order.sort_by_key(|&i| (intervals[i].1, intervals[i].0));
+
+    let mut selected = Vec::new();
+    let mut free_from = isize::MIN;
+    for i in order {
+        if intervals[i].0 >= free_from {
+            selected.push(i);
+            free_from = intervals[i].1;
+        }
+    }
+
+    Ok(IntervalSelection {
+        weight: selected.iter().map(|&i| tasks[i].weight).sum(),
+        tasks: selected,
+    })
+}
+
+// O(n log n) dynamic programming over tasks sorted by finish time. Picks
+// non-overlapping tasks with the greatest total weight.
+pub fn weighted_interval_scheduling(tasks: &[Task]) -> Result<IntervalSelection, ScheduleError> {
+    let intervals = intervals(tasks)?;
+    let mut order: Vec<usize> = (0..tasks.len()).collect();
+    order.sort_by_key(|&i| (intervals[i].1, intervals[i].0));
+    let ends: Vec<isize> = order.iter().map(|&i| intervals[i].1).collect();
+
+    // best[k] is the best weight using only the first k tasks of the order.
+    let mut best = vec![0; order.len() + 1];
+    let mut compatible = vec![0; order.len()];
+    for (k, &i) in order.iter().enumerate() {
+        compatible[k] = ends[..k].partition_point(|&end| end <= intervals[i].0);
+        best[k + 1] = best[k].max(best[compatible[k]] + tasks[i].weight);
+    }
+
+    let mut selected = Vec::new();
+    let mut k = order.len();
+    while k > 0 {
+        if best[k] == best[k - 1] {
+            k -= 1;
+        } else {
+            selected.push(order[k - 1]);
+            k = compatible[k - 1];
+        }
+    }
+    selected.reverse();
+
+    Ok(IntervalSelection {
+        weight: best[order.len()],
+        tasks: selected,
+    })
+}
+
+fn read_tasks(lines: &mut Lines<BufReader<File>>) -> Vec<Task> {
     let count = lines.next().unwrap().unwrap().parse().unwrap();
 
-    let mut heap: BinaryHeap<OptimalTaks> = BinaryHeap::with_capacity(count);
+    let mut tasks = Vec::with_capacity(count);
 
     for line_res in lines {
         if let Ok(line) = line_res {
             let raw_data: Vec<&str> = line.split(" ").collect();
-            let task = OptimalTaks::new(raw_data[0].parse().unwrap(), raw_data[1].parse().unwrap());
-            heap.push(task)
+            tasks.push(Task::new(
+                raw_data[0].parse().unwrap(),
+                raw_data[1].parse().unwrap(),
+            ))
         }
     }
 
-    let mut len = 0;
-    let mut sum = 0;
+    tasks
+}
 
-    while let Some(i) = heap.pop() {
-        len += i.0.len;
-        sum += len * i.0.weight;
-    }
+pub fn schedule(lines: &mut Lines<BufReader<File>>) -> isize {
+    // The difference rule accepts any task.
+    schedule_tasks(&read_tasks(lines), ScheduleRule::Difference)
+        .unwrap()
+        .objective
+}
 
-    sum
+pub fn schedule_optiomal(lines: &mut Lines<BufReader<File>>) -> Result<isize, ScheduleError> {
+    Ok(schedule_tasks(&read_tasks(lines), ScheduleRule::Ratio)?.objective)
 }
 
-#[derive(Eq, Clone, Copy, Debug)]
-struct PrimObject {
-    src: usize,
-    min: isize,
+#[derive(Debug)]
+pub struct SpanningTree {
+    pub edges: Edges,
+    pub weight: isize,
 }
 
-impl PrimObject {
-    pub fn new(src: usize, min: isize) -> Self {
-        PrimObject { src, min }
+// O(m log n). Edges are taken as undirected. When the tree grown from root
+// can't reach every vertex, the search restarts from a vertex left in the
+// heap, so a disconnected graph gives a minimum spanning forest.
+pub fn prim(graph: &SimpleGraph, root: usize) -> SpanningTree {
+    if graph.size == 0 {
+        return SpanningTree {
+            edges: Vec::new(),
+            weight: 0,
+        };
+    }
+    assert!(
+        root < graph.size,
+        "Root {} is out of range for {} vertices",
+        root,
+        graph.size
+    );
+
+    let mut outcome_graph = AdjacencyListGraphOutcome {
+        size: graph.size,
+        is_directed: false,
+        edges: vec![LinkedList::new(); graph.size],
+    };
+    for e in graph.edges.iter() {
+        outcome_graph.add_edge(e);
     }
+
+    let mut heap: UpdateableHeap<usize> = UpdateableHeap::new(graph.size);
+    heap.fill();
+    heap.decrease_by_idx(root, 0, root);
+
+    let mut edges = Vec::with_capacity(graph.size.saturating_sub(1));
+    let mut weight = 0;
+
+    for _i in 0..graph.size {
+        let elem = heap.get_and_remove_min();
+        let vertex = elem.idx;
+
+        if elem.ordering_key != isize::MAX && elem.payload != vertex {
+            edges.push(Edge::new(elem.payload, vertex, elem.ordering_key));
+            weight += elem.ordering_key;
+        }
+
+        for e in outcome_graph.edges[vertex].iter() {
+            heap.decrease_by_idx(e.from_or_to, e.length, vertex);
+        }
+    }
+
+    SpanningTree { edges, weight }
 }
 
-impl PartialEq for PrimObject {
-    fn eq(&self, other: &Self) -> bool {
-        self.min == other.min
+// O(m log m). Edges with equal length are taken in input order.
+pub fn kruskal(graph: &SimpleGraph) -> SpanningTree {
+    let mut order: Vec<usize> = (0..graph.edges.len()).collect();
+    order.sort_by_key(|&i| graph.edges[i].length);
+
+    let mut sets = DisjointSet::new(graph.size);
+    let mut edges = Vec::with_capacity(graph.size.saturating_sub(1));
+    let mut weight = 0;
+
+    for i in order {
+        let e = &graph.edges[i];
+        if sets.union(e.from, e.to) {
+            edges.push(Edge::new(e.from, e.to, e.length));
+            weight += e.length;
+        }
     }
+
+    SpanningTree { edges, weight }
 }
 
-impl PartialOrd for PrimObject {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+// O(m log n). Every round each component takes its cheapest outgoing edge,
+// which at least halves the number of components. The cheapest edges are
+// searched by `threads` threads over disjoint chunks of the edge list.
+pub fn boruvka(graph: &SimpleGraph, threads: usize) -> SpanningTree {
+    let mut sets = DisjointSet::new(graph.size);
+    let mut edges = Vec::with_capacity(graph.size.saturating_sub(1));
+    let mut weight = 0;
+
+    loop {
+        let components: Vec<usize> = (0..graph.size).map(|v| sets.find(v)).collect();
+        let cheapest = cheapest_edges(graph, &components, threads.max(1));
+
+        let mut merged = false;
+        for i in cheapest.into_iter().flatten() {
+            let e = &graph.edges[i];
+            if sets.union(e.from, e.to) {
+                edges.push(Edge::new(e.from, e.to, e.length));
+                weight += e.length;
+                merged = true;
+            }
+        }
+
+        if !merged {
+            break;
+        }
     }
+
+    SpanningTree { edges, weight }
 }
 
-impl Ord for PrimObject {
-    fn cmp(&self, other: &Self) -> Ordering {
-        other.min.cmp(&self.min)
+// Cheapest edge leaving every component, ties broken by edge index so that
+// the chosen edges never close a cycle.
+fn cheapest_edges(graph: &SimpleGraph, components: &[usize], threads: usize) -> Vec<Option<usize>> {
+    let chunk_size = graph.edges.len().div_ceil(threads).max(1);
+
+    let partial: Vec<Vec<Option<usize>>> = thread::scope(|s| {
+        let handles: Vec<_> = (0..graph.edges.len())
+            .step_by(chunk_size)
+            .map(|start| {
+                s.spawn(move || {
+                    let end = (start + chunk_size).min(graph.edges.len());
+                    let mut best = vec![None; graph.size];
+                    for i in start..end {
+                        let e = &graph.edges[i];
+                        let (from, to) = (components[e.from], components[e.to]);
+                        if from != to {
+                            update_cheapest(graph, &mut best, from, i);
+                            update_cheapest(graph, &mut best, to, i);
+                        }
+                    }
+                    best
+                })
+            })
+            .collect();
+
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+
+    let mut best = vec![None; graph.size];
+    for chunk in partial {
+        for (component, edge) in chunk.into_iter().enumerate() {
+            if let Some(i) = edge {
+                update_cheapest(graph, &mut best, component, i);
+            }
+        }
     }
+
+    best
 }
 
-type Graph = Vec<Vec<(usize, isize)>>;
+fn update_cheapest(graph: &SimpleGraph, best: &mut [Option<usize>], component: usize, edge: usize) {
+    let key = |i: usize| (graph.edges[i].length, i);
+    if best[component].is_none_or(|current| key(edge) < key(current)) {
+        best[component] = Some(edge);
+    }
+}
 
-fn build_graph(lines: &mut Lines<BufReader<File>>) -> Graph {
+fn build_graph(lines: &mut Lines<BufReader<File>>) -> SimpleGraph {
     let raw_headers = lines.next().unwrap().unwrap();
     let headers: Vec<&str> = raw_headers.split(" ").collect();
 
     let vertexes_count: usize = headers[0].parse().unwrap();
-    let mut vertexes: Graph = Vec::with_capacity(vertexes_count);
-
-    for _i in 0..vertexes_count {
-        vertexes.push(Vec::new())
-    }
+    let mut edges = Vec::new();
 
     for line_res in lines {
         if let Ok(line) = line_res {
@@ -173,43 +559,15 @@ fn build_graph(lines: &mut Lines<BufReader<File>>) -> Graph {
             let target: usize = raw_data[1].parse().unwrap();
             let minimum: isize = raw_data[2].parse().unwrap();
 
-            vertexes[src - 1].push((target - 1, minimum));
-            vertexes[target - 1].push((src - 1, minimum));
+            edges.push(Edge::new(src - 1, target - 1, minimum));
         }
     }
 
-    vertexes
+    SimpleGraph::new(vertexes_count, edges, false)
 }
 
 pub fn run_prim(lines: &mut Lines<BufReader<File>>) -> isize {
-    let graph = build_graph(lines);
-    let mut heap: BinaryHeap<PrimObject> = BinaryHeap::new();
-
-    let mut remove_idx = 0;
-    let mut sum = 0;
-    let mut removed = HashSet::new();
-    removed.insert(remove_idx);
-
-    for _i in 0..(graph.len() - 1) {
-        for &j in graph[remove_idx].iter() {
-            if !removed.contains(&j.0) {
-                let obj = PrimObject::new(j.0, j.1);
-                heap.push(obj);
-            }
-        }
-
-        let mut min_obj = heap.pop().unwrap();
-
-        while removed.contains(&min_obj.src) {
-            min_obj = heap.pop().unwrap();
-        }
-
-        sum += min_obj.min;
-        remove_idx = min_obj.src;
-        removed.insert(remove_idx);
-    }
-
-    sum
+    prim(&build_graph(lines), 0).weight
 }
 
 #[cfg(test)]
@@ -233,10 +591,160 @@ mod tests {
 
         let reader = BufReader::new(file);
         let mut lines: Lines<BufReader<File>> = reader.lines();
-        let result = schedule_optiomal(&mut lines);
+        let result = schedule_optiomal(&mut lines).unwrap();
         assert_eq!(result, 67_311_454_237);
     }
 
+    #[test]
+    fn schedule_tasks_works() {
+        let tasks = vec![
+            Task::new(3, 5),
+            Task::new(1, 2),
+            Task::new(4, 1),
+            Task::new(2, 2),
+        ];
+
+        let result = schedule_tasks(&tasks, ScheduleRule::Difference).unwrap();
+        assert_eq!(result.order, vec![2, 3, 1, 0]);
+        assert_eq!(result.completion_times, vec![10, 5, 1, 3]);
+        assert_eq!(result.objective, 4 + 2 * 3 + 5 + 3 * 10);
+
+        let result = schedule_tasks(&tasks, ScheduleRule::Ratio).unwrap();
+        assert_eq!(result.order, vec![2, 3, 0, 1]);
+        assert_eq!(result.completion_times, vec![8, 10, 1, 3]);
+        assert_eq!(result.objective, 4 + 2 * 3 + 3 * 8 + 10);
+    }
+
+    #[test]
+    fn schedule_tasks_should_break_ties_by_weight() {
+        let tasks = vec![Task::new(2, 2), Task::new(1, 1), Task::new(2, 2)];
+
+        let result = schedule_tasks(&tasks, ScheduleRule::Difference).unwrap();
+        assert_eq!(result.order, vec![0, 2, 1]);
+
+        let result = schedule_tasks(&tasks, ScheduleRule::Ratio).unwrap();
+        assert_eq!(result.order, vec![0, 2, 1]);
+    }
+
+    #[test]
+    fn schedule_tasks_should_keep_ties_in_order() {
+        let tasks = vec![Task::new(3, 2), Task::new(3, 2), Task::new(3, 2)];
+
+        let result = schedule_tasks(&tasks, ScheduleRule::Difference).unwrap();
+        assert_eq!(result.order, vec![0, 1, 2]);
+
+        let result = schedule_tasks(&tasks, ScheduleRule::Ratio).unwrap();
+        assert_eq!(result.order, vec![0, 1, 2]);
+        assert!(schedule_tasks(&[], ScheduleRule::Ratio)
+            .unwrap()
+            .order
+            .is_empty());
+    }
+
+    #[test]
+    fn ratio_rule_should_be_exact() {
+        // Both ratios round to the same f32, the lighter task goes first.
+        let big = 1 << 40;
+        let tasks = vec![Task::new(big + 1, big), Task::new(big, big - 1)];
+        let result = schedule_tasks(&tasks, ScheduleRule::Ratio).unwrap();
+        assert_eq!(result.order, vec![1, 0]);
+
+        let tasks = vec![Task::new(3, 2), Task::new(1, 0)];
+        assert_eq!(
+            schedule_tasks(&tasks, ScheduleRule::Ratio),
+            Err(ScheduleError::ZeroLength(1))
+        );
+        assert!(schedule_tasks(&tasks, ScheduleRule::Difference).is_ok());
+    }
+
+    #[test]
+    fn tasks_should_compare_all_fields() {
+        assert_eq!(Task::new(1, 2), Task::new(1, 2));
+        assert_ne!(Task::new(1, 2).with_deadline(3), Task::new(1, 2));
+        assert_ne!(
+            Task::new(1, 2).with_release(3),
+            Task::new(1, 2).with_release(4)
+        );
+        assert_ne!(Task::new(2, 3), Task::new(1, 2));
+    }
+
+    #[test]
+    fn deadline_schedules_work() {
+        let tasks = vec![
+            Task::new(1, 2).with_deadline(3),
+            Task::new(1, 4).with_deadline(5),
+            Task::new(1, 3).with_deadline(6),
+            Task::new(1, 1).with_deadline(8),
+            Task::new(1, 3).with_deadline(9),
+        ];
+
+        let result = earliest_deadline_first(&tasks).unwrap();
+        assert_eq!(result.order, vec![0, 1, 2, 3, 4]);
+        assert_eq!(result.completion_times, vec![2, 6, 9, 10, 13]);
+        assert_eq!(result.objective, 4);
+
+        let result = moore_hodgson(&tasks).unwrap();
+        assert_eq!(result.order, vec![0, 2, 3, 4, 1]);
+        assert_eq!(result.completion_times, vec![2, 13, 5, 6, 9]);
+        assert_eq!(result.objective, 1);
+
+        let released = vec![
+            Task::new(1, 3).with_deadline(10).with_release(2),
+            Task::new(1, 2).with_deadline(4),
+            Task::new(1, 1).with_deadline(3).with_release(10),
+            Task::new(1, 2).with_deadline(20).with_release(1),
+        ];
+        let result = earliest_deadline_first(&released).unwrap();
+        assert_eq!(result.order, vec![1, 0, 3, 2]);
+        assert_eq!(result.completion_times, vec![5, 2, 11, 7]);
+        assert_eq!(result.objective, 8);
+        assert!((0..4).all(|i| {
+            result.completion_times[i] - released[i].length >= released[i].release.unwrap_or(0)
+        }));
+        assert_eq!(
+            moore_hodgson(&released),
+            Err(ScheduleError::UnsupportedRelease(0))
+        );
+
+        let early = vec![Task::new(1, 1).with_deadline(5)];
+        assert_eq!(earliest_deadline_first(&early).unwrap().objective, -4);
+        assert_eq!(
+            moore_hodgson(&[Task::new(1, 1).with_deadline(5), Task::new(1, 1)]),
+            Err(ScheduleError::MissingDeadline(1))
+        );
+    }
+
+    #[test]
+    fn interval_schedules_work() {
+        let tasks = vec![
+            Task::new(2, 3).with_release(0),
+            Task::new(4, 4).with_release(1),
+            Task::new(4, 3).with_release(3),
+            Task::new(7, 4).with_release(5),
+            Task::new(2, 2).with_release(6),
+        ];
+
+        let result = interval_scheduling(&tasks).unwrap();
+        assert_eq!(result.tasks, vec![0, 2, 4]);
+        assert_eq!(result.weight, 8);
+
+        let result = weighted_interval_scheduling(&tasks).unwrap();
+        assert_eq!(result.tasks, vec![1, 3]);
+        assert_eq!(result.weight, 11);
+
+        assert_eq!(
+            interval_scheduling(&[Task::new(1, 1)]),
+            Err(ScheduleError::MissingRelease(0))
+        );
+        assert_eq!(
+            weighted_interval_scheduling(&[]),
+            Ok(IntervalSelection {
+                tasks: vec![],
+                weight: 0
+            })
+        );
+    }
+
     #[test]
     fn prim_works() {
         let file = File::open("priv/prim_data.txt").expect("Something went wrong reading the file");
@@ -246,4 +754,97 @@ mod tests {
         let result = run_prim(&mut lines);
         assert_eq!(result, -3_612_829);
     }
+
+    #[test]
+    fn prim_should_return_tree() {
+        let edges = vec![
+            Edge::new(0, 1, 4),
+            Edge::new(0, 2, 1),
+            Edge::new(2, 1, 2),
+            Edge::new(1, 3, 5),
+            Edge::new(2, 3, 8),
+            Edge::new(3, 4, -3),
+        ];
+        let graph = SimpleGraph::new(5, edges, false);
+        let result = prim(&graph, 0);
+        assert_eq!(result.weight, 5);
+        let pairs: Vec<(usize, usize, isize)> = result
+            .edges
+            .iter()
+            .map(|e| (e.from, e.to, e.length))
+            .collect();
+        assert_eq!(pairs, vec![(0, 2, 1), (2, 1, 2), (1, 3, 5), (3, 4, -3)]);
+    }
+
+    #[test]
+    fn prim_should_return_forest() {
+        let edges = vec![
+            Edge::new(0, 1, 4),
+            Edge::new(1, 2, 3),
+            Edge::new(0, 2, 1),
+            Edge::new(3, 4, 7),
+        ];
+        let graph = SimpleGraph::new(6, edges, false);
+        let result = prim(&graph, 4);
+        assert_eq!(result.weight, 11);
+        assert_eq!(result.edges.len(), 3);
+        assert!(result.edges.iter().all(|e| e.length != 4));
+    }
+
+    #[test]
+    fn mst_algos_should_agree() {
+        let file = File::open("priv/prim_data.txt").expect("Something went wrong reading the file");
+
+        let reader = BufReader::new(file);
+        let mut lines: Lines<BufReader<File>> = reader.lines();
+        let graph = build_graph(&mut lines);
+
+        let expected = prim(&graph, 0);
+        for result in [kruskal(&graph), boruvka(&graph, 1), boruvka(&graph, 4)] {
+            assert_eq!(result.weight, -3_612_829);
+            assert_eq!(result.edges.len(), expected.edges.len());
+        }
+    }
+
+    #[test]
+    fn mst_algos_should_handle_empty_graph() {
+        let graph = SimpleGraph::new(0, Vec::new(), false);
+        for result in [prim(&graph, 0), kruskal(&graph), boruvka(&graph, 2)] {
+            assert_eq!(result.weight, 0);
+            assert!(result.edges.is_empty());
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Root 3 is out of range for 3 vertices")]
+    fn prim_should_reject_unknown_root() {
+        let graph = SimpleGraph::new(3, vec![Edge::new(0, 1, 1)], false);
+        prim(&graph, 3);
+    }
+
+    #[test]
+    fn mst_algos_should_return_forest() {
+        let edges = vec![
+            Edge::new(0, 1, 4),
+            Edge::new(1, 2, 3),
+            Edge::new(0, 2, 1),
+            Edge::new(3, 4, 7),
+            Edge::new(4, 5, 7),
+            Edge::new(3, 5, 7),
+        ];
+        let graph = SimpleGraph::new(7, edges, false);
+
+        let kruskal_result = kruskal(&graph);
+        let pairs: Vec<(usize, usize)> = kruskal_result
+            .edges
+            .iter()
+            .map(|e| (e.from, e.to))
+            .collect();
+        assert_eq!(pairs, vec![(0, 2), (1, 2), (3, 4), (4, 5)]);
+
+        for result in [kruskal_result, boruvka(&graph, 1), boruvka(&graph, 3)] {
+            assert_eq!(result.weight, 18);
+            assert_eq!(result.edges.len(), 4);
+        }
+    }
 }
@@ -0,0 +1,239 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet};
+
+use topological_sort::Grpah;
+
+use crate::{schedule_tasks, ScheduleError, ScheduleRule, Task};
+
+#[derive(Debug, PartialEq)]
+pub struct MachineSchedule {
+    // Task indices every machine runs, in execution order.
+    pub machines: Vec<Vec<usize>>,
+    // Start and completion time of every task, indexed like the input.
+    pub start_times: Vec<isize>,
+    pub completion_times: Vec<isize>,
+    pub makespan: isize,
+    // What the heuristic minimises: the makespan for lpt and list_scheduling,
+    // the sum of weight * completion time for weighted_ratio.
+    pub objective: isize,
+    // Worst case objective / optimal objective guaranteed by the heuristic.
+    pub bound: f64,
+}
+
+impl MachineSchedule {
+    fn new(tasks: usize, machines: usize, bound: f64) -> Self {
+        MachineSchedule {
+            machines: vec![Vec::new(); machines],
+            start_times: vec![0; tasks],
+            completion_times: vec![0; tasks],
+            makespan: 0,
+            objective: 0,
+            bound,
+        }
+    }
+
+    fn assign(&mut self, task: usize, length: isize, machine: usize, start: isize) -> isize {
+        let completion = start + length;
+        self.machines[machine].push(task);
+        self.start_times[task] = start;
+        self.completion_times[task] = completion;
+        self.makespan = self.makespan.max(completion);
+        completion
+    }
+}
+
+// Every task in order goes to the machine that becomes free first, the lowest
+// index on ties.
+fn assign_in_order(
+    tasks: &[Task],
+    machines: usize,
+    order: &[usize],
+    bound: f64,
+) -> MachineSchedule {
+    assert!(machines > 0, "no machines");
+
+    let mut result = MachineSchedule::new(tasks.len(), machines, bound);
+    let mut free: BinaryHeap<Reverse<(isize, usize)>> =
+        (0..machines).map(|m| Reverse((0, m))).collect();
+
+    for &i in order {
+        let Reverse((start, machine)) = free.pop().unwrap();
+        let completion = result.assign(i, tasks[i].length, machine, start);
+        free.push(Reverse((completion, machine)));
+    }
+
+    result
+}
+
+// Longest processing time first. Makespan within 4/3 - 1/(3m) of optimal.
+pub fn lpt(tasks: &[Task], machines: usize) -> MachineSchedule {
+    let mut order: Vec<usize> = (0..tasks.len()).collect();
+    order.sort_by_key(|&i| Reverse(tasks[i].length));
+
+    let bound = 4.0 / 3.0 - 1.0 / (3.0 * machines as f64);
+    let mut result = assign_in_order(tasks, machines, &order, bound);
+    result.objective = result.makespan;
+    result
+}
+
+// Graham's list scheduling, O(n * (n + p + m)) for p precedences: every
+// placement scans the tasks with their predecessors and may first wait for
+// a few completions. `precedences` are (before, after) pairs, a task starts
+// only once all its predecessors are complete. A free machine takes the
+// lowest index task that is ready, and waits for the next completion when
+// none is. Makespan within 2 - 1/m of optimal. Fails with
+// ScheduleError::UnknownTask for a precedence naming no task and with
+// ScheduleError::Cycle when the precedences aren't acyclic.
+pub fn list_scheduling(
+    tasks: &[Task],
+    machines: usize,
+    precedences: &[(usize, usize)],
+) -> Result<MachineSchedule, ScheduleError> {
+    assert!(machines > 0, "no machines");
+
+    if let Some(&(before, after)) = precedences
+        .iter()
+        .find(|&&(before, after)| before.max(after) >= tasks.len())
+    {
+        let i = if before >= tasks.len() { before } else { after };
+        return Err(ScheduleError::UnknownTask(i));
+    }
+
+    let edges_set: HashSet<(usize, usize)> = precedences.iter().copied().collect();
+    Grpah::new(tasks.len(), &edges_set)
+        .order()
+        .ok_or(ScheduleError::Cycle)?;
+
+    let mut predecessors = vec![Vec::new(); tasks.len()];
+    for &(before, after) in edges_set.iter() {
+        predecessors[after].push(before);
+    }
+
+    let bound = 2.0 - 1.0 / machines as f64;
+    let mut result = MachineSchedule::new(tasks.len(), machines, bound);
+    let mut free = vec![0; machines];
+    let mut is_scheduled = vec![false; tasks.len()];
+
+    for _ in 0..tasks.len() {
+        loop {
+            let machine = (0..machines).min_by_key(|&m| (free[m], m)).unwrap();
+            let now = free[machine];
+            let is_ready = |i: usize| {
+                !is_scheduled[i]
+                    && predecessors[i]
+                        .iter()
+                        .all(|&p| is_scheduled[p] && result.completion_times[p] <= now)
+            };
+
+            if let Some(i) = (0..tasks.len()).find(|&i| is_ready(i)) {
+                free[machine] = result.assign(i, tasks[i].length, machine, now);
+                is_scheduled[i] = true;
+                break;
+            }
+
+            // The first unscheduled task of a topological order has all its
+            // predecessors scheduled, so one of them is still running.
+            free[machine] = (0..tasks.len())
+                .filter(|&i| is_scheduled[i] && result.completion_times[i] > now)
+                .map(|i| result.completion_times[i])
+                .min()
+                .unwrap();
+        }
+    }
+
+    result.objective = result.makespan;
+    Ok(result)
+}
+
+// Greatest weight / length first on the machine that becomes free first.
+// Sum of weight * completion time within (1 + sqrt(2)) / 2 of optimal
+// (Kawaguchi and Kyan). Rejects tasks of length 0 like schedule_tasks.
+pub fn weighted_ratio(tasks: &[Task], machines: usize) -> Result<MachineSchedule, ScheduleError> {
+    let order = schedule_tasks(tasks, ScheduleRule::Ratio)?.order;
+
+    let bound = (1.0 + 2f64.sqrt()) / 2.0;
+    let mut result = assign_in_order(tasks, machines, &order, bound);
+    result.objective = tasks
+        .iter()
+        .zip(result.completion_times.iter())
+        .map(|(t, c)| t.weight * c)
+        .sum();
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tasks(lengths: &[isize]) -> Vec<Task> {
+        lengths.iter().map(|&l| Task::new(1, l)).collect()
+    }
+
+    #[test]
+    fn lpt_works() {
+        let result = lpt(&tasks(&[2, 3, 7, 2, 5, 4]), 3);
+        assert_eq!(result.machines, vec![vec![2, 3], vec![4, 0], vec![5, 1]]);
+        assert_eq!(result.completion_times, vec![7, 7, 7, 9, 5, 4]);
+        assert_eq!(result.makespan, 9);
+        assert_eq!(result.objective, 9);
+        assert!((result.bound - 11.0 / 9.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn lpt_should_stay_within_bound() {
+        // The tight instance: optimal makespan is 9, LPT gives 11.
+        let machines = 3;
+        let result = lpt(&tasks(&[5, 5, 4, 4, 3, 3, 3]), machines);
+        assert_eq!(result.makespan, 11);
+        assert!(result.makespan as f64 <= result.bound * 9.0 + 1e-9);
+    }
+
+    #[test]
+    fn list_scheduling_works() {
+        let precedences = [(0, 2), (1, 2), (2, 4), (3, 4)];
+        let result = list_scheduling(&tasks(&[3, 1, 2, 4, 1]), 2, &precedences).unwrap();
+        assert_eq!(result.machines, vec![vec![0, 2, 4], vec![1, 3]]);
+        assert_eq!(result.start_times, vec![0, 0, 3, 1, 5]);
+        assert_eq!(result.makespan, 6);
+        assert!((result.bound - 1.5).abs() < 1e-9);
+
+        for &(before, after) in precedences.iter() {
+            assert!(result.completion_times[before] <= result.start_times[after]);
+        }
+    }
+
+    #[test]
+    fn list_scheduling_should_reject_cycles() {
+        let result = list_scheduling(&tasks(&[1, 1, 1]), 2, &[(0, 1), (1, 2), (2, 0)]);
+        assert_eq!(result, Err(ScheduleError::Cycle));
+    }
+
+    #[test]
+    fn list_scheduling_should_reject_unknown_tasks() {
+        let result = list_scheduling(&tasks(&[1, 1, 1]), 2, &[(0, 1), (1, 3)]);
+        assert_eq!(result, Err(ScheduleError::UnknownTask(3)));
+
+        let result = list_scheduling(&tasks(&[1, 1, 1]), 2, &[(7, 1)]);
+        assert_eq!(result, Err(ScheduleError::UnknownTask(7)));
+    }
+
+    #[test]
+    fn weighted_ratio_works() {
+        let tasks = vec![
+            Task::new(3, 3),
+            Task::new(4, 1),
+            Task::new(1, 2),
+            Task::new(6, 2),
+        ];
+        let result = weighted_ratio(&tasks, 2).unwrap();
+        assert_eq!(result.machines, vec![vec![1, 0], vec![3, 2]]);
+        assert_eq!(result.completion_times, vec![4, 1, 4, 2]);
+        assert_eq!(result.makespan, 4);
+        assert_eq!(result.objective, 3 * 4 + 4 + 4 + 6 * 2);
+
+        assert_eq!(
+            weighted_ratio(&[Task::new(1, 0)], 2),
+            Err(ScheduleError::ZeroLength(0))
+        );
+    }
+}
@@ -1,45 +1,172 @@
 use bellman_ford::bellman_ford;
-use dijkstra::dijkstra_with_reweighting;
+use dijkstra::{dijkstra_tree_with_reweighting, dijkstra_with_reweighting};
+use floyd_warshall::floyd_warshall;
 use graph_representations::{
-    AdjacencyListGraphIncome, AdjacencyListGraphOutcome, Edge, SimpleGraph,
+    AdjacencyListEdge, AdjacencyListGraphIncome, AdjacencyListGraphOutcome, DistanceMatrix,
+    SimpleGraph,
 };
+use std::collections::LinkedList;
+use std::thread;
 
-pub fn jonsons(graph: &mut SimpleGraph) -> Option<Vec<Vec<isize>>> {
-    let last_idx = graph.size;
-    graph.increase_size(1);
-    for i in 0..last_idx {
-        graph.add_edge(Edge {
-            from: last_idx,
-            to: i,
-            length: 0,
+// Vertex potentials found by Bellman-Ford from a virtual vertex together with
+// the reweighted copy of the graph. Every reweighted edge is non-negative, so
+// later queries only need Dijkstra.
+#[derive(Debug)]
+pub struct JohnsonPotential {
+    potential: Vec<isize>,
+    graph: AdjacencyListGraphOutcome,
+}
+
+impl JohnsonPotential {
+    // None when the graph has a negative cycle. The input graph is not touched.
+    pub fn new(graph: &SimpleGraph) -> Option<Self> {
+        let virtual_idx = graph.size;
+        let mut income_graph = AdjacencyListGraphIncome::from_simple(graph);
+        income_graph.size += 1;
+        income_graph.edges.push(LinkedList::new());
+        for i in 0..virtual_idx {
+            income_graph.edges[i].push_back(AdjacencyListEdge {
+                length: 0,
+                from_or_to: virtual_idx,
+            });
+        }
+
+        let mut potential = bellman_ford(&income_graph, virtual_idx)?;
+        potential.truncate(virtual_idx);
+
+        let mut outcome_graph = AdjacencyListGraphOutcome::from_simple(graph);
+        for (from, edges) in outcome_graph.edges.iter_mut().enumerate() {
+            for e in edges.iter_mut() {
+                e.length += potential[from] - potential[e.from_or_to];
+            }
+        }
+
+        Some(JohnsonPotential {
+            potential,
+            graph: outcome_graph,
         })
     }
 
-    let mut adjacency_graph = AdjacencyListGraphIncome::from_simple(graph);
+    pub fn size(&self) -> usize {
+        self.graph.size
+    }
 
-    if let Some(v) = bellman_ford(&mut adjacency_graph, last_idx) {
-        graph.decrease_size(1);
-        reweight_graph(graph, &v);
-        let mut res = Vec::with_capacity(graph.size);
-        let outcome_graph = AdjacencyListGraphOutcome::from_simple(&graph);
-        for i in 0..graph.size {
-            res.push(dijkstra_with_reweighting(&outcome_graph, i, &v))
+    pub fn potential(&self) -> &[isize] {
+        &self.potential
+    }
+
+    // Real (not reweighted) distances, isize::MAX for unreachable vertices.
+    pub fn shortest_paths(&self, source: usize) -> Vec<isize> {
+        dijkstra_with_reweighting(&self.graph, source, &self.potential)
+    }
+
+    pub fn distance(&self, source: usize, target: usize) -> Option<isize> {
+        let distance = self.shortest_paths(source)[target];
+        if distance == isize::MAX {
+            None
+        } else {
+            Some(distance)
+        }
+    }
+
+    // Columns are computed on the reversed graph: the shortest path tree into
+    // a target gives consistent next hops for every vertex of its column.
+    pub fn all_pairs(&self) -> DistanceMatrix {
+        let size = self.size();
+        let reversed_graph = self.reversed_graph();
+        let reversed_potential: Vec<isize> = self.potential.iter().map(|p| -p).collect();
+        let columns = all_sources_dijkstra(&reversed_graph, &reversed_potential);
+
+        let mut matrix = DistanceMatrix::new(size);
+        for (to, (distances, next_hops)) in columns.into_iter().enumerate() {
+            for from in 0..size {
+                if from != to && distances[from] != isize::MAX {
+                    matrix.set(from, to, Some(distances[from]), next_hops[from]);
+                }
+            }
         }
-        Some(res)
+
+        matrix
+    }
+
+    fn reversed_graph(&self) -> AdjacencyListGraphOutcome {
+        let mut edges = vec![LinkedList::new(); self.size()];
+        for (from, from_edges) in self.graph.edges.iter().enumerate() {
+            for e in from_edges.iter() {
+                edges[e.from_or_to].push_back(AdjacencyListEdge {
+                    length: e.length,
+                    from_or_to: from,
+                });
+            }
+        }
+
+        AdjacencyListGraphOutcome {
+            size: self.size(),
+            is_directed: self.graph.is_directed,
+            edges,
+        }
+    }
+}
+
+pub fn jonsons(graph: &SimpleGraph) -> Option<DistanceMatrix> {
+    JohnsonPotential::new(graph).map(|potential| potential.all_pairs())
+}
+
+// Picks Floyd-Warshall for dense graphs and Johnson otherwise.
+pub fn all_pairs(graph: &SimpleGraph) -> Option<DistanceMatrix> {
+    if prefer_floyd_warshall(graph) {
+        floyd_warshall(graph)
     } else {
-        None
+        jonsons(graph)
     }
 }
 
-fn reweight_graph(graph: &mut SimpleGraph, reweight_vec: &Vec<isize>) {
-    for e in graph.edges.iter_mut() {
-        e.length += reweight_vec[e.from] - reweight_vec[e.to];
+// Johnson does n Dijkstra runs, about n * m * log(n), while Floyd-Warshall
+// is n^3 with a much smaller constant. Past m ~ n^2 / log(n) the cubic DP wins.
+pub fn prefer_floyd_warshall(graph: &SimpleGraph) -> bool {
+    let size = graph.size;
+    if size < 2 {
+        return true;
     }
+
+    let edges = if graph.is_directed {
+        graph.edges.len()
+    } else {
+        graph.edges.len() * 2
+    };
+
+    edges * size.ilog2() as usize >= size * size
+}
+
+// Sources are split into contiguous chunks, one per thread. Every tree is
+// written only by the thread owning its source, so the output is the same
+// as running the sources one after another.
+fn all_sources_dijkstra(
+    graph: &AdjacencyListGraphOutcome,
+    reweight_vec: &[isize],
+) -> Vec<(Vec<isize>, Vec<Option<usize>>)> {
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk_size = graph.size.div_ceil(threads).max(1);
+    let mut res = vec![(Vec::new(), Vec::new()); graph.size];
+
+    thread::scope(|s| {
+        for (chunk_idx, trees) in res.chunks_mut(chunk_size).enumerate() {
+            s.spawn(move || {
+                for (offset, tree) in trees.iter_mut().enumerate() {
+                    let source = chunk_idx * chunk_size + offset;
+                    *tree = dijkstra_tree_with_reweighting(graph, source, reweight_vec);
+                }
+            });
+        }
+    });
+
+    res
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use graph_representations::Edge;
     use std::fs::File;
     use std::isize::MAX;
 
@@ -86,60 +213,175 @@ mod tests {
             },
         ];
 
-        let mut graph = SimpleGraph::new(6, edges, true);
-        let result = jonsons(&mut graph).unwrap();
-        assert_eq!(result[0], vec![0, -2, -3, -6, MAX, -1]);
+        let graph = SimpleGraph::new(6, edges, true);
+        let result = jonsons(&graph).unwrap();
+        assert_eq!(
+            result.row(0).collect::<Vec<_>>(),
+            vec![Some(0), Some(-2), Some(-3), Some(-6), None, Some(-1)]
+        );
         assert_eq!(
-            result,
+            (0..6)
+                .map(|i| result.row(i).collect())
+                .collect::<Vec<Vec<_>>>(),
             vec![
-                vec![0, -2, -3, -6, MAX, -1],
-                vec![3, 0, -1, -4, MAX, 1],
-                vec![4, 2, 0, -3, MAX, 2],
-                vec![MAX, MAX, MAX, 0, MAX, MAX],
-                vec![MAX, MAX, MAX, -4, 0, 1],
-                vec![MAX, MAX, MAX, MAX, MAX, 0]
+                vec![Some(0), Some(-2), Some(-3), Some(-6), None, Some(-1)],
+                vec![Some(3), Some(0), Some(-1), Some(-4), None, Some(1)],
+                vec![Some(4), Some(2), Some(0), Some(-3), None, Some(2)],
+                vec![None, None, None, Some(0), None, None],
+                vec![None, None, None, Some(-4), Some(0), Some(1)],
+                vec![None, None, None, None, None, Some(0)]
             ]
         );
+        assert_eq!(result.path(1, 0), Some(vec![1, 2, 0]));
+        assert_eq!(result.path(0, 3), Some(vec![0, 1, 2, 3]));
+        assert_eq!(result.path(4, 5), Some(vec![4, 5]));
+        assert_eq!(result.path(3, 4), None);
     }
 
     #[test]
     fn it_works_first() {
-        let mut graph = graph_from_file("priv/first_graph.txt");
-        let result = jonsons(&mut graph);
+        let graph = graph_from_file("priv/first_graph.txt");
+        let result = jonsons(&graph);
         assert_eq!(result, None);
     }
 
     #[test]
     fn it_works_second() {
-        let mut graph = graph_from_file("priv/second_graph.txt");
-        let result = jonsons(&mut graph);
+        let graph = graph_from_file("priv/second_graph.txt");
+        let result = jonsons(&graph);
         assert_eq!(result, None);
     }
 
     #[test]
     fn it_works_third() {
-        let mut graph = graph_from_file("priv/third_graph.txt");
-        let result = jonsons(&mut graph).unwrap();
+        let graph = graph_from_file("priv/third_graph.txt");
+        let result = jonsons(&graph).unwrap();
         let mut min = MAX;
 
         for i in 0..graph.size {
             for j in 0..graph.size {
-                let v = result[i][j];
+                let v = result.get(i, j).unwrap_or(MAX);
                 min = if v < min { v } else { min }
             }
         }
         assert_eq!(min, -19);
     }
 
+    #[test]
+    fn parallel_matches_sequential() {
+        let graph = graph_from_file("priv/third_graph.txt");
+        let result = jonsons(&graph).unwrap();
+        let potential = JohnsonPotential::new(&graph).unwrap();
+
+        for i in 0..graph.size {
+            let row: Vec<isize> = result.row(i).map(|v| v.unwrap_or(MAX)).collect();
+            assert_eq!(row, potential.shortest_paths(i));
+        }
+    }
+
+    #[test]
+    fn should_not_change_graph() {
+        let graph = graph_from_file("priv/third_graph.txt");
+        let result = jonsons(&graph);
+        assert!(result.is_some());
+        assert_eq!(graph.size, 1000);
+        assert_eq!(graph.edges.len(), 47978);
+        assert_eq!((graph.edges[0].from, graph.edges[0].to), (0, 7));
+        assert_eq!(graph.edges[0].length, 36);
+
+        let graph = graph_from_file("priv/first_graph.txt");
+        assert_eq!(jonsons(&graph), None);
+        assert_eq!(graph.size, 1000);
+        assert_eq!(graph.edges.len(), 47978);
+    }
+
+    #[test]
+    fn potential_should_answer_queries() {
+        let edges = vec![
+            Edge::new(0, 1, -2),
+            Edge::new(1, 2, -1),
+            Edge::new(2, 0, 4),
+            Edge::new(2, 3, -3),
+            Edge::new(4, 3, -4),
+            Edge::new(4, 5, 1),
+            Edge::new(2, 5, 2),
+        ];
+
+        let graph = SimpleGraph::new(6, edges, true);
+        let potential = JohnsonPotential::new(&graph).unwrap();
+        assert_eq!(potential.potential(), &[0, -2, -3, -6, 0, -1]);
+        assert_eq!(potential.shortest_paths(1), vec![3, 0, -1, -4, MAX, 1]);
+        assert_eq!(potential.distance(4, 3), Some(-4));
+        assert_eq!(potential.distance(3, 4), None);
+        assert_eq!(potential.distance(2, 2), Some(0));
+    }
+
+    #[test]
+    fn should_match_floyd_warshall() {
+        let graph = graph_from_file("priv/third_graph.txt");
+        let result = jonsons(&graph).unwrap();
+        let expected = floyd_warshall(&graph).unwrap();
+
+        for i in 0..graph.size {
+            assert!(result.row(i).eq(expected.row(i)));
+        }
+
+        for (from, to) in [(0, 999), (17, 3), (500, 250)] {
+            let path = result.path(from, to).unwrap();
+            let length: isize = path
+                .windows(2)
+                .map(|w| {
+                    graph
+                        .edges
+                        .iter()
+                        .filter(|e| e.from == w[0] && e.to == w[1])
+                        .map(|e| e.length)
+                        .min()
+                        .unwrap()
+                })
+                .sum();
+            assert_eq!(Some(length), expected.get(from, to));
+        }
+    }
+
+    #[test]
+    fn should_pick_by_density() {
+        let sparse = graph_from_file("priv/third_graph.txt");
+        assert!(!prefer_floyd_warshall(&sparse));
+
+        let mut edges = Vec::new();
+        for i in 0..50 {
+            for j in 0..50 {
+                if i != j {
+                    edges.push(Edge::new(i, j, ((i * 7 + j * 13) % 17) as isize));
+                }
+            }
+        }
+        let dense = SimpleGraph::new(50, edges, true);
+        assert!(prefer_floyd_warshall(&dense));
+
+        let result = all_pairs(&dense).unwrap();
+        let expected = jonsons(&dense).unwrap();
+        for i in 0..dense.size {
+            assert!(result.row(i).eq(expected.row(i)));
+        }
+
+        let result = all_pairs(&sparse).unwrap();
+        let expected = floyd_warshall(&sparse).unwrap();
+        for i in 0..sparse.size {
+            assert!(result.row(i).eq(expected.row(i)));
+        }
+    }
+
     #[test]
     fn it_works_huge() {
-        let mut graph = graph_from_file("priv/huge_graph.txt");
-        let result = jonsons(&mut graph).unwrap();
+        let graph = graph_from_file("priv/huge_graph.txt");
+        let result = jonsons(&graph).unwrap();
         let mut min = MAX;
 
         for i in 0..graph.size {
             for j in 0..graph.size {
-                let v = result[i][j];
+                let v = result.get(i, j).unwrap_or(MAX);
                 min = if v < min { v } else { min }
             }
         }
@@ -0,0 +1,415 @@
+use std::collections::VecDeque;
+
+use graph_representations::SimpleGraph;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct MaxFlow {
+    pub value: isize,
+    // Flow on every input edge in input order. On undirected graphs a
+    // negative flow goes from `to` to `from`.
+    pub flows: Vec<isize>,
+    // Vertices still reachable from the source in the residual graph, the
+    // source side of a minimum s-t cut.
+    pub source_side: Vec<bool>,
+}
+
+// Every input edge becomes the arcs 2 * i (along the edge) and 2 * i + 1
+// (against it). Edge lengths are used as capacities and must be non-negative.
+struct ResidualGraph {
+    size: usize,
+    heads: Vec<usize>,
+    capacities: Vec<isize>,
+    initial: Vec<isize>,
+    arcs: Vec<Vec<usize>>,
+}
+
+impl ResidualGraph {
+    fn from_simple(graph: &SimpleGraph) -> Self {
+        let mut heads = Vec::with_capacity(graph.edges.len() * 2);
+        let mut capacities = Vec::with_capacity(graph.edges.len() * 2);
+        let mut arcs = vec![Vec::new(); graph.size];
+
+        for e in graph.edges.iter() {
+            assert!(e.length >= 0, "negative capacity {}", e.length);
+
+            arcs[e.from].push(heads.len());
+            heads.push(e.to);
+            capacities.push(e.length);
+
+            arcs[e.to].push(heads.len());
+            heads.push(e.from);
+            capacities.push(if graph.is_directed { 0 } else { e.length });
+        }
+
+        ResidualGraph {
+            size: graph.size,
+            heads,
+            initial: capacities.clone(),
+            capacities,
+            arcs,
+        }
+    }
+
+    fn push(&mut self, arc: usize, value: isize) {
+        self.capacities[arc] -= value;
+        self.capacities[arc ^ 1] += value;
+    }
+
+    // BFS distances in arcs from source over arcs with spare capacity.
+    fn levels(&self, source: usize) -> Vec<Option<usize>> {
+        let mut levels = vec![None; self.size];
+        let mut queue = VecDeque::new();
+        levels[source] = Some(0);
+        queue.push_back(source);
+
+        while let Some(v) = queue.pop_front() {
+            let level = levels[v].unwrap();
+            for &arc in self.arcs[v].iter() {
+                let to = self.heads[arc];
+                if self.capacities[arc] > 0 && levels[to].is_none() {
+                    levels[to] = Some(level + 1);
+                    queue.push_back(to);
+                }
+            }
+        }
+
+        levels
+    }
+
+    fn into_result(self, source: usize, value: isize) -> MaxFlow {
+        let source_side = self.levels(source).iter().map(|l| l.is_some()).collect();
+        let flows = (0..self.heads.len() / 2)
+            .map(|i| self.initial[2 * i] - self.capacities[2 * i])
+            .collect();
+
+        MaxFlow {
+            value,
+            flows,
+            source_side,
+        }
+    }
+}
+
+// O(n * m^2). Augments along shortest paths found by BFS.
+pub fn edmonds_karp(graph: &SimpleGraph, source: usize, sink: usize) -> MaxFlow {
+    let mut residual = ResidualGraph::from_simple(graph);
+    let mut value = 0;
+
+    loop {
+        let mut parent_arc: Vec<Option<usize>> = vec![None; residual.size];
+        let mut visited = vec![false; residual.size];
+        let mut queue = VecDeque::new();
+        visited[source] = true;
+        queue.push_back(source);
+
+        while let Some(v) = queue.pop_front() {
+            if v == sink {
+                break;
+            }
+            for &arc in residual.arcs[v].iter() {
+                let to = residual.heads[arc];
+                if residual.capacities[arc] > 0 && !visited[to] {
+                    visited[to] = true;
+                    parent_arc[to] = Some(arc);
+                    queue.push_back(to);
+                }
+            }
+        }
+
+        if !visited[sink] || source == sink {
+            break;
+        }
+
+        let mut bottleneck = isize::MAX;
+        let mut v = sink;
+        while let Some(arc) = parent_arc[v] {
+            bottleneck = bottleneck.min(residual.capacities[arc]);
+            v = residual.heads[arc ^ 1];
+        }
+
+        let mut v = sink;
+        while let Some(arc) = parent_arc[v] {
+            residual.push(arc, bottleneck);
+            v = residual.heads[arc ^ 1];
+        }
+
+        value += bottleneck;
+    }
+
+    residual.into_result(source, value)
+}
+
+// O(n^2 * m). Blocking flows over the BFS level graph.
+pub fn dinic(graph: &SimpleGraph, source: usize, sink: usize) -> MaxFlow {
+    let mut residual = ResidualGraph::from_simple(graph);
+    let mut value = 0;
+
+    if source == sink {
+        return residual.into_result(source, value);
+    }
+
+    loop {
+        let levels = residual.levels(source);
+        if levels[sink].is_none() {
+            break;
+        }
+
+        value += blocking_flow(&mut residual, &levels, source, sink);
+    }
+
+    residual.into_result(source, value)
+}
+
+// Saturates every source-sink path of the level graph. The current path is
+// kept as a stack of arcs rather than as recursion, so the search depth is
+// not limited by the thread's stack.
+fn blocking_flow(
+    residual: &mut ResidualGraph,
+    levels: &[Option<usize>],
+    source: usize,
+    sink: usize,
+) -> isize {
+    let mut current_arc = vec![0; residual.size];
+    let mut path: Vec<usize> = Vec::new();
+    let mut total = 0;
+
+    loop {
+        let v = path.last().map_or(source, |&arc| residual.heads[arc]);
+
+        if v == sink {
+            let bottleneck = path
+                .iter()
+                .map(|&arc| residual.capacities[arc])
+                .min()
+                .unwrap();
+            for &arc in path.iter() {
+                residual.push(arc, bottleneck);
+            }
+            total += bottleneck;
+
+            // Continue from the tail of the first saturated arc.
+            let saturated = path
+                .iter()
+                .position(|&arc| residual.capacities[arc] == 0)
+                .unwrap();
+            path.truncate(saturated);
+            continue;
+        }
+
+        let mut next = None;
+        while current_arc[v] < residual.arcs[v].len() {
+            let arc = residual.arcs[v][current_arc[v]];
+            let to = residual.heads[arc];
+            if residual.capacities[arc] > 0 && levels[to] == levels[v].map(|l| l + 1) {
+                next = Some(arc);
+                break;
+            }
+            current_arc[v] += 1;
+        }
+
+        match next {
+            Some(arc) => path.push(arc),
+            // Dead end: drop the arc leading here for this phase.
+            None => match path.pop() {
+                Some(arc) => current_arc[residual.heads[arc ^ 1]] += 1,
+                None => return total,
+            },
+        }
+    }
+}
+
+// O(n^3). FIFO push-relabel, works on a preflow instead of augmenting paths.
+pub fn push_relabel(graph: &SimpleGraph, source: usize, sink: usize) -> MaxFlow {
+    let mut residual = ResidualGraph::from_simple(graph);
+    let size = residual.size;
+    let mut heights = vec![0; size];
+    let mut excess = vec![0; size];
+    let mut current_arc = vec![0; size];
+    let mut active = VecDeque::new();
+
+    if source != sink {
+        heights[source] = size;
+        for i in 0..residual.arcs[source].len() {
+            let arc = residual.arcs[source][i];
+            let to = residual.heads[arc];
+            let capacity = residual.capacities[arc];
+            if capacity > 0 {
+                residual.push(arc, capacity);
+                excess[to] += capacity;
+                excess[source] -= capacity;
+                if to != sink && to != source && excess[to] == capacity {
+                    active.push_back(to);
+                }
+            }
+        }
+    }
+
+    while let Some(v) = active.pop_front() {
+        while excess[v] > 0 {
+            if current_arc[v] == residual.arcs[v].len() {
+                heights[v] = 1 + residual.arcs[v]
+                    .iter()
+                    .filter(|&&arc| residual.capacities[arc] > 0)
+                    .map(|&arc| heights[residual.heads[arc]])
+                    .min()
+                    .unwrap();
+                current_arc[v] = 0;
+                continue;
+            }
+
+            let arc = residual.arcs[v][current_arc[v]];
+            let to = residual.heads[arc];
+            if residual.capacities[arc] > 0 && heights[v] == heights[to] + 1 {
+                let value = excess[v].min(residual.capacities[arc]);
+                residual.push(arc, value);
+                excess[v] -= value;
+                if to != source && to != sink && excess[to] == 0 {
+                    active.push_back(to);
+                }
+                excess[to] += value;
+            } else {
+                current_arc[v] += 1;
+            }
+        }
+    }
+
+    residual.into_result(source, excess[sink])
+}
+
+#[cfg(test)]
+mod tests {
+    use graph_representations::Edge;
+
+    use super::*;
+
+    type Algo = fn(&SimpleGraph, usize, usize) -> MaxFlow;
+
+    const ALGOS: [Algo; 3] = [edmonds_karp, dinic, push_relabel];
+
+    fn check_flow(graph: &SimpleGraph, result: &MaxFlow, source: usize, sink: usize) {
+        let mut balance = vec![0; graph.size];
+        let mut cut = 0;
+        for (e, &flow) in graph.edges.iter().zip(result.flows.iter()) {
+            if graph.is_directed {
+                assert!(0 <= flow && flow <= e.length);
+            } else {
+                assert!(flow.abs() <= e.length);
+            }
+            balance[e.from] -= flow;
+            balance[e.to] += flow;
+
+            if result.source_side[e.from] && !result.source_side[e.to] {
+                cut += e.length;
+            }
+            if !graph.is_directed && result.source_side[e.to] && !result.source_side[e.from] {
+                cut += e.length;
+            }
+        }
+
+        for (v, &b) in balance.iter().enumerate() {
+            if v != source && v != sink {
+                assert_eq!(b, 0);
+            }
+        }
+        assert_eq!(balance[sink], result.value);
+        assert!(result.source_side[source]);
+        assert!(!result.source_side[sink]);
+        assert_eq!(cut, result.value);
+    }
+
+    #[test]
+    fn it_works() {
+        let edges = vec![
+            Edge::new(0, 1, 16),
+            Edge::new(0, 2, 13),
+            Edge::new(2, 1, 4),
+            Edge::new(1, 3, 12),
+            Edge::new(3, 2, 9),
+            Edge::new(2, 4, 14),
+            Edge::new(4, 3, 7),
+            Edge::new(3, 5, 20),
+            Edge::new(4, 5, 4),
+        ];
+        let graph = SimpleGraph::new(6, edges, true);
+
+        for algo in ALGOS {
+            let result = algo(&graph, 0, 5);
+            assert_eq!(result.value, 23);
+            assert_eq!(
+                result.source_side,
+                vec![true, true, true, false, true, false]
+            );
+            check_flow(&graph, &result, 0, 5);
+        }
+    }
+
+    #[test]
+    fn it_works_undirected() {
+        let edges = vec![
+            Edge::new(0, 1, 3),
+            Edge::new(1, 2, 2),
+            Edge::new(2, 3, 3),
+            Edge::new(0, 2, 1),
+            Edge::new(3, 1, 4),
+            Edge::new(4, 3, 5),
+        ];
+        let graph = SimpleGraph::new(5, edges, false);
+
+        for algo in ALGOS {
+            let result = algo(&graph, 3, 0);
+            assert_eq!(result.value, 4);
+            assert_eq!(result.flows[0], -3);
+            check_flow(&graph, &result, 3, 0);
+        }
+    }
+
+    #[test]
+    fn should_handle_disconnected_sink() {
+        let edges = vec![Edge::new(0, 1, 3), Edge::new(2, 1, 3)];
+        let graph = SimpleGraph::new(3, edges, true);
+
+        for algo in ALGOS {
+            let result = algo(&graph, 0, 2);
+            assert_eq!(result.value, 0);
+            assert_eq!(result.flows, vec![0, 0]);
+            assert_eq!(result.source_side, vec![true, true, false]);
+        }
+    }
+
+    #[test]
+    fn should_handle_long_path() {
+        let size = 100_000;
+        let edges = (1..size).map(|i| Edge::new(i - 1, i, 1)).collect();
+        let graph = SimpleGraph::new(size, edges, true);
+
+        for algo in ALGOS {
+            let result = algo(&graph, 0, size - 1);
+            assert_eq!(result.value, 1);
+            assert!(result.flows.iter().all(|&f| f == 1));
+        }
+    }
+
+    #[test]
+    fn algos_should_agree() {
+        let size = 40;
+        let mut edges = Vec::new();
+        for i in 0..size {
+            for j in 0..size {
+                let c = (i * 31 + j * 17 + i * j) % 23;
+                if i != j && c < 6 {
+                    edges.push(Edge::new(i, j, c as isize));
+                }
+            }
+        }
+        let graph = SimpleGraph::new(size, edges, true);
+
+        for sink in 1..size {
+            let expected = edmonds_karp(&graph, 0, sink);
+            for algo in ALGOS {
+                let result = algo(&graph, 0, sink);
+                assert_eq!(result.value, expected.value);
+                check_flow(&graph, &result, 0, sink);
+            }
+        }
+    }
+}
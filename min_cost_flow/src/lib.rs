@@ -0,0 +1,346 @@
+use dijkstra::dijkstra_tree_with_reweighting;
+use graph_representations::{AdjacencyListGraphOutcome, Edge, SimpleGraph};
+use jonsons::JohnsonPotential;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlowEdge {
+    pub from: usize,
+    pub to: usize,
+    pub capacity: isize,
+    pub cost: isize,
+}
+
+impl FlowEdge {
+    pub fn new(from: usize, to: usize, capacity: isize, cost: isize) -> Self {
+        FlowEdge {
+            from,
+            to,
+            capacity,
+            cost,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct MinCostFlow {
+    pub value: isize,
+    pub cost: isize,
+    // Flow on every input edge in input order.
+    pub flows: Vec<isize>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Assignment {
+    pub cost: isize,
+    // Column assigned to every row.
+    pub columns: Vec<usize>,
+}
+
+// Every edge becomes the arcs 2 * i (along it, with its cost) and 2 * i + 1
+// (against it, with the negated cost).
+struct ResidualGraph {
+    size: usize,
+    heads: Vec<usize>,
+    capacities: Vec<isize>,
+    costs: Vec<isize>,
+    arcs: Vec<Vec<usize>>,
+}
+
+impl ResidualGraph {
+    fn new(size: usize, edges: &[FlowEdge]) -> Self {
+        let mut graph = ResidualGraph {
+            size,
+            heads: Vec::with_capacity(edges.len() * 2),
+            capacities: Vec::with_capacity(edges.len() * 2),
+            costs: Vec::with_capacity(edges.len() * 2),
+            arcs: vec![Vec::new(); size],
+        };
+
+        for e in edges {
+            assert!(e.capacity >= 0, "negative capacity {}", e.capacity);
+            graph.add_arc(e.from, e.to, e.capacity, e.cost);
+            graph.add_arc(e.to, e.from, 0, -e.cost);
+        }
+
+        graph
+    }
+
+    fn add_arc(&mut self, from: usize, to: usize, capacity: isize, cost: isize) {
+        self.arcs[from].push(self.heads.len());
+        self.heads.push(to);
+        self.capacities.push(capacity);
+        self.costs.push(cost);
+    }
+
+    // Arcs with spare capacity, lengths shifted by the potential.
+    fn reduced_graph(&self, potential: &[isize]) -> AdjacencyListGraphOutcome {
+        AdjacencyListGraphOutcome::from_simple(&self.simple_graph(potential))
+    }
+
+    fn simple_graph(&self, potential: &[isize]) -> SimpleGraph {
+        let mut edges = Vec::new();
+        for from in 0..self.size {
+            for &arc in self.arcs[from].iter() {
+                if self.capacities[arc] > 0 {
+                    let to = self.heads[arc];
+                    let length = self.costs[arc] + potential[from] - potential[to];
+                    edges.push(Edge::new(from, to, length));
+                }
+            }
+        }
+
+        SimpleGraph::new(self.size, edges, true)
+    }
+
+    // Cheapest arc with spare capacity between two vertices.
+    fn cheapest_arc(&self, from: usize, to: usize) -> usize {
+        *self.arcs[from]
+            .iter()
+            .filter(|&&arc| self.heads[arc] == to && self.capacities[arc] > 0)
+            .min_by_key(|&&arc| self.costs[arc])
+            .unwrap()
+    }
+}
+
+pub fn min_cost_max_flow(
+    size: usize,
+    edges: &[FlowEdge],
+    source: usize,
+    sink: usize,
+) -> Option<MinCostFlow> {
+    min_cost_flow(size, edges, source, sink, isize::MAX)
+}
+
+// Successive shortest paths: Bellman-Ford once (through JohnsonPotential) for
+// the initial potential, then Dijkstra on reduced costs for every augmenting
+// path. Sends at most `limit` units. None if the costs have a negative cycle.
+pub fn min_cost_flow(
+    size: usize,
+    edges: &[FlowEdge],
+    source: usize,
+    sink: usize,
+    limit: isize,
+) -> Option<MinCostFlow> {
+    let mut residual = ResidualGraph::new(size, edges);
+    let initial = JohnsonPotential::new(&residual.simple_graph(&vec![0; size]))?;
+    let mut potential = initial.potential().to_vec();
+    let mut value = 0;
+    let mut cost = 0;
+
+    while value < limit && source != sink {
+        let reduced_graph = residual.reduced_graph(&potential);
+        let (distances, parents) =
+            dijkstra_tree_with_reweighting(&reduced_graph, source, &potential);
+        if distances[sink] == isize::MAX {
+            break;
+        }
+
+        let mut path = Vec::new();
+        let mut v = sink;
+        while let Some(parent) = parents[v] {
+            path.push(residual.cheapest_arc(parent, v));
+            v = parent;
+        }
+
+        let bottleneck = path
+            .iter()
+            .map(|&arc| residual.capacities[arc])
+            .min()
+            .unwrap()
+            .min(limit - value);
+
+        for &arc in path.iter() {
+            residual.capacities[arc] -= bottleneck;
+            residual.capacities[arc ^ 1] += bottleneck;
+        }
+
+        value += bottleneck;
+        cost += bottleneck * distances[sink];
+
+        for (p, d) in potential.iter_mut().zip(distances.iter()) {
+            if *d != isize::MAX {
+                *p = *d;
+            }
+        }
+    }
+
+    let flows = (0..edges.len())
+        .map(|i| residual.capacities[2 * i + 1])
+        .collect();
+
+    Some(MinCostFlow { value, cost, flows })
+}
+
+// Hungarian algorithm, O(n^2 * m) for n rows and m >= n columns. Assigns a
+// distinct column to every row minimising the total cost.
+pub fn hungarian(costs: &[Vec<isize>]) -> Assignment {
+    let rows = costs.len();
+    let columns = if rows == 0 { 0 } else { costs[0].len() };
+    assert!(rows <= columns, "more rows than columns");
+
+    // 1-based: row 0 and column 0 are the virtual start of every search.
+    let mut row_potential = vec![0; rows + 1];
+    let mut column_potential = vec![0; columns + 1];
+    let mut column_row = vec![0; columns + 1];
+    let mut way = vec![0; columns + 1];
+
+    for row in 1..=rows {
+        column_row[0] = row;
+        let mut column = 0;
+        let mut min_slack = vec![isize::MAX; columns + 1];
+        let mut used = vec![false; columns + 1];
+
+        loop {
+            used[column] = true;
+            let current_row = column_row[column];
+            let mut delta = isize::MAX;
+            let mut next_column = 0;
+
+            for j in 1..=columns {
+                if used[j] {
+                    continue;
+                }
+                let slack = costs[current_row - 1][j - 1]
+                    - row_potential[current_row]
+                    - column_potential[j];
+                if slack < min_slack[j] {
+                    min_slack[j] = slack;
+                    way[j] = column;
+                }
+                if min_slack[j] < delta {
+                    delta = min_slack[j];
+                    next_column = j;
+                }
+            }
+
+            for j in 0..=columns {
+                if used[j] {
+                    row_potential[column_row[j]] += delta;
+                    column_potential[j] -= delta;
+                } else {
+                    min_slack[j] -= delta;
+                }
+            }
+
+            column = next_column;
+            if column_row[column] == 0 {
+                break;
+            }
+        }
+
+        while column != 0 {
+            let prev = way[column];
+            column_row[column] = column_row[prev];
+            column = prev;
+        }
+    }
+
+    let mut assigned = vec![0; rows];
+    for j in 1..=columns {
+        if column_row[j] != 0 {
+            assigned[column_row[j] - 1] = j - 1;
+        }
+    }
+
+    Assignment {
+        cost: (0..rows).map(|i| costs[i][assigned[i]]).sum(),
+        columns: assigned,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_works() {
+        let edges = vec![
+            FlowEdge::new(0, 1, 4, 2),
+            FlowEdge::new(0, 2, 2, 2),
+            FlowEdge::new(1, 2, 2, 1),
+            FlowEdge::new(1, 3, 3, 3),
+            FlowEdge::new(2, 3, 5, 1),
+        ];
+
+        let result = min_cost_max_flow(4, &edges, 0, 3).unwrap();
+        assert_eq!(result.value, 6);
+        assert_eq!(result.cost, 2 * 3 + 2 * 4 + 2 * 5);
+        assert_eq!(result.flows, vec![4, 2, 2, 2, 4]);
+
+        let result = min_cost_flow(4, &edges, 0, 3, 3).unwrap();
+        assert_eq!(result.value, 3);
+        assert_eq!(result.cost, 2 * 3 + 4);
+    }
+
+    #[test]
+    fn it_works_with_negative_costs() {
+        let edges = vec![
+            FlowEdge::new(0, 1, 2, 1),
+            FlowEdge::new(1, 2, 2, -5),
+            FlowEdge::new(0, 2, 2, -1),
+            FlowEdge::new(2, 3, 3, 0),
+        ];
+
+        let result = min_cost_max_flow(4, &edges, 0, 3).unwrap();
+        assert_eq!(result.value, 3);
+        assert_eq!(result.cost, 2 * -4 - 1);
+        assert_eq!(result.flows, vec![2, 2, 1, 3]);
+    }
+
+    #[test]
+    fn should_reject_negative_cycle() {
+        let edges = vec![
+            FlowEdge::new(0, 1, 2, 1),
+            FlowEdge::new(1, 2, 2, -2),
+            FlowEdge::new(2, 1, 2, 1),
+        ];
+
+        assert_eq!(min_cost_max_flow(3, &edges, 0, 2), None);
+    }
+
+    #[test]
+    fn hungarian_works() {
+        let costs = vec![vec![4, 1, 3], vec![2, 0, 5], vec![3, 2, 2]];
+        let result = hungarian(&costs);
+        assert_eq!(
+            result,
+            Assignment {
+                cost: 5,
+                columns: vec![1, 0, 2],
+            }
+        );
+
+        let costs = vec![vec![7, 3, 9, 1], vec![2, 8, 4, 6]];
+        let result = hungarian(&costs);
+        assert_eq!(result.cost, 3);
+        assert_eq!(result.columns, vec![3, 0]);
+    }
+
+    #[test]
+    fn hungarian_should_match_flow() {
+        let size = 7;
+        let costs: Vec<Vec<isize>> = (0..size)
+            .map(|i| {
+                (0..size)
+                    .map(|j| ((i * 37 + j * 11 + i * j * 5) % 19) as isize - 4)
+                    .collect()
+            })
+            .collect();
+
+        let source = 2 * size;
+        let sink = 2 * size + 1;
+        let mut edges = Vec::new();
+        for (i, row) in costs.iter().enumerate() {
+            edges.push(FlowEdge::new(source, i, 1, 0));
+            edges.push(FlowEdge::new(size + i, sink, 1, 0));
+            for (j, &cost) in row.iter().enumerate() {
+                edges.push(FlowEdge::new(i, size + j, 1, cost));
+            }
+        }
+
+        let flow = min_cost_max_flow(2 * size + 2, &edges, source, sink).unwrap();
+        let result = hungarian(&costs);
+        assert_eq!(flow.value, size as isize);
+        assert_eq!(flow.cost, result.cost);
+    }
+}
@@ -90,6 +90,8 @@ mod tests {
     use std::fs;
 
     use crate::min_cut;
+    use graph_representations::{Edge, SimpleGraph};
+    use max_flow::dinic;
 
     #[test]
     fn it_works_small() {
@@ -123,6 +125,41 @@ mod tests {
         assert_eq!(2, g);
     }
 
+    #[test]
+    fn it_works_with_max_flow() {
+        let edges = vec![
+            (0, 4),
+            (0, 7),
+            (1, 2),
+            (1, 3),
+            (1, 4),
+            (2, 3),
+            (2, 6),
+            (2, 10),
+            (3, 5),
+            (4, 5),
+            (4, 9),
+            (4, 10),
+            (5, 6),
+            (5, 9),
+            (6, 7),
+            (6, 8),
+            (7, 8),
+            (8, 11),
+            (8, 12),
+            (8, 9),
+            (9, 10),
+            (11, 12),
+        ];
+        let graph = SimpleGraph::new(
+            13,
+            edges.iter().map(|&(a, b)| Edge::new(a, b, 1)).collect(),
+            false,
+        );
+        let exact = (1..13).map(|t| dinic(&graph, 0, t).value).min().unwrap();
+        assert_eq!(exact as usize, min_cut(edges, 13));
+    }
+
     #[test]
     fn it_works_file() {
         let contents =
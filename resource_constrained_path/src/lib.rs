@@ -0,0 +1,246 @@
+use graph_representations::MultiWeightGraph;
+use updatable_heap::UpdateableHeap;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParetoPath {
+    pub cost: isize,
+    pub resources: Vec<isize>,
+    pub path: Vec<usize>,
+}
+
+#[derive(Debug)]
+struct Label {
+    vertex: usize,
+    cost: isize,
+    resources: Vec<isize>,
+    parent: Option<usize>,
+}
+
+impl Label {
+    fn dominates(&self, other: &Label) -> bool {
+        self.cost <= other.cost
+            && self
+                .resources
+                .iter()
+                .zip(other.resources.iter())
+                .all(|(a, b)| a <= b)
+    }
+}
+
+// Label-setting search. weights[0] of every edge is its cost, weights[1..]
+// are the resources it consumes, each limited by the matching budget entry.
+// All weights must be non-negative. Returns the Pareto front of paths from
+// source to target sorted by cost: every path is cheaper than the next one
+// and uses less of some resource than each cheaper one.
+pub fn resource_constrained_paths(
+    graph: &MultiWeightGraph,
+    source: usize,
+    target: usize,
+    budget: &[isize],
+) -> Vec<ParetoPath> {
+    for e in graph.edges.iter() {
+        assert_eq!(
+            e.weights.len(),
+            budget.len() + 1,
+            "edge {}-{} needs a cost and {} resources",
+            e.from,
+            e.to,
+            budget.len()
+        );
+        assert!(
+            e.weights.iter().all(|&w| w >= 0),
+            "negative weight on edge {}-{}",
+            e.from,
+            e.to
+        );
+    }
+
+    let outcome_edges = graph.outcome_edges();
+    let mut labels = vec![Label {
+        vertex: source,
+        cost: 0,
+        resources: vec![0; budget.len()],
+        parent: None,
+    }];
+    let mut settled: Vec<Vec<usize>> = vec![Vec::new(); graph.size];
+    let mut heap: UpdateableHeap<()> = UpdateableHeap::new(0);
+    heap.push(0, ());
+
+    while !heap.is_empty() {
+        let label_id = heap.get_and_remove_min().idx;
+        let vertex = labels[label_id].vertex;
+
+        if settled[vertex]
+            .iter()
+            .any(|&i| labels[i].dominates(&labels[label_id]))
+        {
+            continue;
+        }
+        settled[vertex].push(label_id);
+
+        if vertex == target {
+            continue;
+        }
+
+        for &edge_idx in outcome_edges[vertex].iter() {
+            let edge = &graph.edges[edge_idx];
+            let to = if edge.from == vertex {
+                edge.to
+            } else {
+                edge.from
+            };
+            let label = &labels[label_id];
+
+            let resources: Vec<isize> = label
+                .resources
+                .iter()
+                .zip(edge.weights[1..].iter())
+                .map(|(a, b)| a + b)
+                .collect();
+            if resources.iter().zip(budget.iter()).any(|(r, b)| r > b) {
+                continue;
+            }
+
+            let new_label = Label {
+                vertex: to,
+                cost: label.cost + edge.weights[0],
+                resources,
+                parent: Some(label_id),
+            };
+            if settled[to].iter().any(|&i| labels[i].dominates(&new_label)) {
+                continue;
+            }
+
+            heap.push(new_label.cost, ());
+            labels.push(new_label);
+        }
+    }
+
+    // Labels with equal cost leave the heap in any order, so a settled label
+    // may still be dominated by one settled after it. Equal labels are never
+    // both settled.
+    let front: Vec<usize> = settled[target]
+        .iter()
+        .copied()
+        .filter(|&i| {
+            !settled[target]
+                .iter()
+                .any(|&j| j != i && labels[j].dominates(&labels[i]))
+        })
+        .collect();
+
+    let mut result: Vec<ParetoPath> = front
+        .into_iter()
+        .map(|i| ParetoPath {
+            cost: labels[i].cost,
+            resources: labels[i].resources.clone(),
+            path: build_path(&labels, i),
+        })
+        .collect();
+    result.sort_by(|a, b| a.cost.cmp(&b.cost).then(a.resources.cmp(&b.resources)));
+
+    result
+}
+
+fn build_path(labels: &[Label], label_id: usize) -> Vec<usize> {
+    let mut path = vec![labels[label_id].vertex];
+    let mut current = labels[label_id].parent;
+    while let Some(i) = current {
+        path.push(labels[i].vertex);
+        current = labels[i].parent;
+    }
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use graph_representations::MultiWeightEdge;
+
+    use super::*;
+
+    fn build_graph() -> MultiWeightGraph {
+        let edges = vec![
+            MultiWeightEdge::new(0, 1, vec![1, 5]),
+            MultiWeightEdge::new(0, 2, vec![4, 1]),
+            MultiWeightEdge::new(1, 3, vec![1, 5]),
+            MultiWeightEdge::new(2, 3, vec![4, 1]),
+            MultiWeightEdge::new(1, 2, vec![1, 1]),
+            MultiWeightEdge::new(2, 1, vec![0, 0]),
+        ];
+        MultiWeightGraph::new(5, edges, true)
+    }
+
+    #[test]
+    fn it_works() {
+        let graph = build_graph();
+        let result = resource_constrained_paths(&graph, 0, 3, &[10]);
+        assert_eq!(
+            result,
+            vec![
+                ParetoPath {
+                    cost: 2,
+                    resources: vec![10],
+                    path: vec![0, 1, 3]
+                },
+                ParetoPath {
+                    cost: 5,
+                    resources: vec![6],
+                    path: vec![0, 2, 1, 3]
+                },
+                ParetoPath {
+                    cost: 8,
+                    resources: vec![2],
+                    path: vec![0, 2, 3]
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn should_respect_budget() {
+        let graph = build_graph();
+        let result = resource_constrained_paths(&graph, 0, 3, &[7]);
+        let front: Vec<(isize, Vec<isize>)> =
+            result.into_iter().map(|p| (p.cost, p.resources)).collect();
+        assert_eq!(front, vec![(5, vec![6]), (8, vec![2])]);
+
+        assert!(resource_constrained_paths(&graph, 0, 3, &[1]).is_empty());
+        assert!(resource_constrained_paths(&graph, 0, 4, &[100]).is_empty());
+    }
+
+    #[test]
+    fn it_works_with_many_resources() {
+        let edges = vec![
+            MultiWeightEdge::new(0, 1, vec![1, 1, 3]),
+            MultiWeightEdge::new(1, 2, vec![1, 1, 3]),
+            MultiWeightEdge::new(0, 2, vec![3, 1, 1]),
+            MultiWeightEdge::new(0, 2, vec![3, 3, 1]),
+        ];
+        let graph = MultiWeightGraph::new(3, edges, false);
+        let result = resource_constrained_paths(&graph, 2, 0, &[5, 6]);
+        let front: Vec<(isize, Vec<isize>, Vec<usize>)> = result
+            .into_iter()
+            .map(|p| (p.cost, p.resources, p.path))
+            .collect();
+        assert_eq!(
+            front,
+            vec![(2, vec![2, 6], vec![2, 1, 0]), (3, vec![1, 1], vec![2, 0])]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "needs a cost and 2 resources")]
+    fn should_reject_missing_resources() {
+        let graph = build_graph();
+        resource_constrained_paths(&graph, 0, 3, &[10, 10]);
+    }
+
+    #[test]
+    #[should_panic(expected = "negative weight")]
+    fn should_reject_negative_weights() {
+        let edges = vec![MultiWeightEdge::new(0, 1, vec![1, -1])];
+        let graph = MultiWeightGraph::new(2, edges, true);
+        resource_constrained_paths(&graph, 0, 1, &[10]);
+    }
+}
@@ -44,24 +44,55 @@ impl Grpah {
         &self.edges_set
     }
 
-    pub fn sort(&mut self) -> bool {
+    // Vertices in topological order, None if the graph has a cycle. The DFS
+    // keeps its own stack, so long paths can't overflow the thread's one.
+    pub fn order(&self) -> Option<Vec<usize>> {
         let mut colors_vec = vec![Color::White; self.size];
-        let mut result = vec![];
+        let mut result = Vec::with_capacity(self.size);
+        // Vertex and the index of its next edge to follow.
+        let mut stack: Vec<(usize, usize)> = Vec::new();
 
         for i in 0..self.size {
-            let color = colors_vec[i];
-            if color == Color::White {
-                if !self.dfs_sort(i, &mut colors_vec, &mut result) {
-                    return false;
+            if colors_vec[i] != Color::White {
+                continue;
+            }
+
+            colors_vec[i] = Color::Gray;
+            stack.push((i, 0));
+
+            while let Some((v, next)) = stack.last_mut() {
+                let v = *v;
+                if *next == self.edges[v].len() {
+                    colors_vec[v] = Color::Black;
+                    result.push(v);
+                    stack.pop();
+                    continue;
+                }
+
+                let to = self.edges[v][*next];
+                *next += 1;
+                match colors_vec[to] {
+                    Color::White => {
+                        colors_vec[to] = Color::Gray;
+                        stack.push((to, 0));
+                    }
+                    Color::Gray => return None,
+                    Color::Black => (),
                 }
-                result.push(i)
-            } else if color == Color::Gray {
-                return false;
             }
         }
 
         result.reverse();
 
+        Some(result)
+    }
+
+    pub fn sort(&mut self) -> bool {
+        let result = match self.order() {
+            Some(v) => v,
+            None => return false,
+        };
+
         let mut new_set = HashSet::new();
 
         for edge in self.edges_set.iter() {
@@ -73,29 +104,6 @@ impl Grpah {
 
         true
     }
-
-    fn dfs_sort(&self, edge_idx: usize, colors: &mut Vec<Color>, result: &mut Vec<usize>) -> bool {
-        colors[edge_idx] = Color::Gray;
-
-        let edge = &self.edges[edge_idx];
-
-        for from in 0..edge.len() {
-            let to = edge[from];
-            let color = colors[to];
-            if color == Color::White {
-                if !self.dfs_sort(to, colors, result) {
-                    return false;
-                }
-                result.push(to);
-            } else if color == Color::Gray {
-                return false;
-            }
-        }
-
-        colors[edge_idx] = Color::Black;
-
-        return true;
-    }
 }
 
 #[cfg(test)]
@@ -138,4 +146,23 @@ mod tests {
         let result2 = g.edges().clone();
         assert_eq!(result1, result2);
     }
+
+    #[test]
+    fn should_return_order() {
+        let e = HashSet::from([(0, 3), (3, 2), (3, 1), (2, 1)]);
+        let g = Grpah::new(4, &e);
+        assert_eq!(g.order(), Some(vec![0, 3, 2, 1]));
+
+        let e = HashSet::from([(0, 3), (3, 2), (3, 1), (2, 1), (1, 3)]);
+        let g = Grpah::new(4, &e);
+        assert_eq!(g.order(), None);
+    }
+
+    #[test]
+    fn should_order_long_chain() {
+        let size = 200_000;
+        let e: HashSet<(usize, usize)> = (1..size).map(|i| (i - 1, i)).collect();
+        let g = Grpah::new(size, &e);
+        assert_eq!(g.order(), Some((0..size).collect()));
+    }
 }
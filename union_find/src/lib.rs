@@ -2,6 +2,7 @@ use std::{
     cell::RefCell,
     cmp::Ordering::{Equal, Greater, Less},
     rc::Rc,
+    sync::atomic::{AtomicUsize, Ordering},
 };
 
 pub type Node<T> = Rc<RefCell<UnionFind<T>>>;
@@ -60,6 +61,489 @@ impl<T: PartialEq> UnionFind<T> {
     }
 }
 
+// Dense disjoint sets over 0..size. Union by size, iterative path
+// compression, amortised O(alpha(n)) per operation.
+#[derive(Debug, Clone)]
+pub struct DisjointSet {
+    parents: Vec<usize>,
+    sizes: Vec<usize>,
+    sets: usize,
+}
+
+impl DisjointSet {
+    pub fn new(size: usize) -> Self {
+        DisjointSet {
+            parents: (0..size).collect(),
+            sizes: vec![1; size],
+            sets: size,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parents.is_empty()
+    }
+
+    pub fn find(&mut self, x: usize) -> usize {
+        let mut root = x;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+
+        let mut current = x;
+        while self.parents[current] != root {
+            let next = self.parents[current];
+            self.parents[current] = root;
+            current = next;
+        }
+
+        root
+    }
+
+    // False if x and y were already in the same set.
+    pub fn union(&mut self, x: usize, y: usize) -> bool {
+        let mut x_root = self.find(x);
+        let mut y_root = self.find(y);
+        if x_root == y_root {
+            return false;
+        }
+
+        if self.sizes[x_root] < self.sizes[y_root] {
+            std::mem::swap(&mut x_root, &mut y_root);
+        }
+        self.parents[y_root] = x_root;
+        self.sizes[x_root] += self.sizes[y_root];
+        self.sets -= 1;
+
+        true
+    }
+
+    pub fn same_set(&mut self, x: usize, y: usize) -> bool {
+        self.find(x) == self.find(y)
+    }
+
+    pub fn set_size(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.sizes[root]
+    }
+
+    pub fn count_sets(&self) -> usize {
+        self.sets
+    }
+}
+
+// DisjointSet where every set carries an aggregate. Each element starts with
+// its own value, union combines the aggregates of both sets through `merge`,
+// always called as merge(set of x, set of y).
+pub struct AggregateDisjointSet<A, F> {
+    sets: DisjointSet,
+    aggregates: Vec<Option<A>>,
+    merge: F,
+}
+
+impl<A, F: FnMut(A, A) -> A> AggregateDisjointSet<A, F> {
+    pub fn new(values: Vec<A>, merge: F) -> Self {
+        AggregateDisjointSet {
+            sets: DisjointSet::new(values.len()),
+            aggregates: values.into_iter().map(Some).collect(),
+            merge,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.sets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sets.is_empty()
+    }
+
+    pub fn find(&mut self, x: usize) -> usize {
+        self.sets.find(x)
+    }
+
+    // False if x and y were already in the same set.
+    pub fn union(&mut self, x: usize, y: usize) -> bool {
+        let x_root = self.sets.find(x);
+        let y_root = self.sets.find(y);
+        if !self.sets.union(x_root, y_root) {
+            return false;
+        }
+
+        let x_aggregate = self.aggregates[x_root].take().unwrap();
+        let y_aggregate = self.aggregates[y_root].take().unwrap();
+        let root = self.sets.find(x_root);
+        self.aggregates[root] = Some((self.merge)(x_aggregate, y_aggregate));
+
+        true
+    }
+
+    pub fn same_set(&mut self, x: usize, y: usize) -> bool {
+        self.sets.same_set(x, y)
+    }
+
+    pub fn set_size(&mut self, x: usize) -> usize {
+        self.sets.set_size(x)
+    }
+
+    pub fn count_sets(&self) -> usize {
+        self.sets.count_sets()
+    }
+
+    // Aggregate of the set containing x.
+    pub fn aggregate(&mut self, x: usize) -> &A {
+        let root = self.sets.find(x);
+        self.aggregates[root].as_ref().unwrap()
+    }
+}
+
+// Number of unions made so far, see RollbackUnionFind::snapshot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Snapshot(usize);
+
+#[derive(Debug, Clone)]
+struct UnionRecord {
+    child: usize,
+    parent: usize,
+    rank_increased: bool,
+}
+
+// Disjoint sets over 0..size whose unions can be undone. Union by rank
+// without path compression keeps every find O(log n) and lets rollback
+// restore the exact previous state.
+#[derive(Debug, Clone)]
+pub struct RollbackUnionFind {
+    parents: Vec<usize>,
+    ranks: Vec<usize>,
+    sizes: Vec<usize>,
+    history: Vec<UnionRecord>,
+}
+
+impl RollbackUnionFind {
+    pub fn new(size: usize) -> Self {
+        RollbackUnionFind {
+            parents: (0..size).collect(),
+            ranks: vec![0; size],
+            sizes: vec![1; size],
+            history: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parents.is_empty()
+    }
+
+    pub fn find(&self, x: usize) -> usize {
+        let mut root = x;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+        root
+    }
+
+    // False if x and y were already in the same set. Only merging unions
+    // are recorded.
+    pub fn union(&mut self, x: usize, y: usize) -> bool {
+        let mut parent = self.find(x);
+        let mut child = self.find(y);
+        if parent == child {
+            return false;
+        }
+
+        if self.ranks[parent] < self.ranks[child] {
+            std::mem::swap(&mut parent, &mut child);
+        }
+        let rank_increased = self.ranks[parent] == self.ranks[child];
+
+        self.parents[child] = parent;
+        self.sizes[parent] += self.sizes[child];
+        if rank_increased {
+            self.ranks[parent] += 1;
+        }
+        self.history.push(UnionRecord {
+            child,
+            parent,
+            rank_increased,
+        });
+
+        true
+    }
+
+    pub fn same_set(&self, x: usize, y: usize) -> bool {
+        self.find(x) == self.find(y)
+    }
+
+    pub fn set_size(&self, x: usize) -> usize {
+        self.sizes[self.find(x)]
+    }
+
+    pub fn count_sets(&self) -> usize {
+        self.len() - self.history.len()
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot(self.history.len())
+    }
+
+    // Undoes the last merging union, false if there is none.
+    pub fn undo(&mut self) -> bool {
+        let record = match self.history.pop() {
+            Some(v) => v,
+            None => return false,
+        };
+
+        self.parents[record.child] = record.child;
+        self.sizes[record.parent] -= self.sizes[record.child];
+        if record.rank_increased {
+            self.ranks[record.parent] -= 1;
+        }
+
+        true
+    }
+
+    // Undoes every union made after the snapshot was taken. Panics if the
+    // snapshot is newer than the current state.
+    pub fn rollback(&mut self, snapshot: Snapshot) {
+        assert!(
+            snapshot.0 <= self.history.len(),
+            "snapshot {} is ahead of {} unions",
+            snapshot.0,
+            self.history.len()
+        );
+
+        while self.history.len() > snapshot.0 {
+            self.undo();
+        }
+    }
+}
+
+// A constraint contradicting the ones already known: x - y was `known`
+// but `requested` was asked for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PotentialConflict {
+    pub known: isize,
+    pub requested: isize,
+}
+
+// Disjoint sets over 0..size tracking constraints "x - y = d". Every element
+// stores its potential relative to its parent, which path compression turns
+// into the potential relative to the set leader.
+#[derive(Debug, Clone)]
+pub struct PotentialUnionFind {
+    parents: Vec<usize>,
+    sizes: Vec<usize>,
+    potentials: Vec<isize>,
+    sets: usize,
+}
+
+impl PotentialUnionFind {
+    pub fn new(size: usize) -> Self {
+        PotentialUnionFind {
+            parents: (0..size).collect(),
+            sizes: vec![1; size],
+            potentials: vec![0; size],
+            sets: size,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parents.is_empty()
+    }
+
+    pub fn find(&mut self, x: usize) -> usize {
+        let mut path = Vec::new();
+        let mut root = x;
+        while self.parents[root] != root {
+            path.push(root);
+            root = self.parents[root];
+        }
+
+        // Nearest to the leader first, so the parent is already relative to it.
+        for &v in path.iter().rev() {
+            let parent = self.parents[v];
+            if parent != root {
+                self.potentials[v] += self.potentials[parent];
+                self.parents[v] = root;
+            }
+        }
+
+        root
+    }
+
+    // x - leader of x.
+    fn potential(&mut self, x: usize) -> isize {
+        let root = self.find(x);
+        if root == x {
+            0
+        } else {
+            self.potentials[x]
+        }
+    }
+
+    // Adds the constraint x - y = d. Ok(false) if it already followed from
+    // the known ones, Err if it contradicts them.
+    pub fn union(&mut self, x: usize, y: usize, d: isize) -> Result<bool, PotentialConflict> {
+        let x_root = self.find(x);
+        let y_root = self.find(y);
+        let x_potential = self.potential(x);
+        let y_potential = self.potential(y);
+
+        if x_root == y_root {
+            let known = x_potential - y_potential;
+            return if known == d {
+                Ok(false)
+            } else {
+                Err(PotentialConflict {
+                    known,
+                    requested: d,
+                })
+            };
+        }
+
+        // x_root - y_root
+        let roots_diff = d - x_potential + y_potential;
+        if self.sizes[x_root] < self.sizes[y_root] {
+            self.parents[x_root] = y_root;
+            self.potentials[x_root] = roots_diff;
+            self.sizes[y_root] += self.sizes[x_root];
+        } else {
+            self.parents[y_root] = x_root;
+            self.potentials[y_root] = -roots_diff;
+            self.sizes[x_root] += self.sizes[y_root];
+        }
+        self.sets -= 1;
+
+        Ok(true)
+    }
+
+    // x - y, None if no constraints connect them.
+    pub fn diff(&mut self, x: usize, y: usize) -> Option<isize> {
+        if self.find(x) != self.find(y) {
+            return None;
+        }
+
+        Some(self.potential(x) - self.potential(y))
+    }
+
+    pub fn same_set(&mut self, x: usize, y: usize) -> bool {
+        self.find(x) == self.find(y)
+    }
+
+    pub fn set_size(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.sizes[root]
+    }
+
+    pub fn count_sets(&self) -> usize {
+        self.sets
+    }
+}
+
+// Lock-free disjoint sets over 0..size, safe to share between threads. A
+// root is linked under the root with the greater index by CAS, so links never
+// form a cycle, and find halves paths as it walks them.
+#[derive(Debug)]
+pub struct ConcurrentDisjointSet {
+    parents: Vec<AtomicUsize>,
+    sets: AtomicUsize,
+}
+
+impl ConcurrentDisjointSet {
+    pub fn new(size: usize) -> Self {
+        ConcurrentDisjointSet {
+            parents: (0..size).map(AtomicUsize::new).collect(),
+            sets: AtomicUsize::new(size),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parents.is_empty()
+    }
+
+    fn parent(&self, x: usize) -> usize {
+        self.parents[x].load(Ordering::Acquire)
+    }
+
+    // The leader may already be stale when it's returned if other threads
+    // keep merging.
+    pub fn find(&self, x: usize) -> usize {
+        let mut current = x;
+        loop {
+            let parent = self.parent(current);
+            if parent == current {
+                return current;
+            }
+
+            let grandparent = self.parent(parent);
+            // Losing the race only means someone else shortened the path.
+            let _ = self.parents[current].compare_exchange_weak(
+                parent,
+                grandparent,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            );
+            current = grandparent;
+        }
+    }
+
+    // False if x and y were already in the same set.
+    pub fn union(&self, x: usize, y: usize) -> bool {
+        loop {
+            let x_root = self.find(x);
+            let y_root = self.find(y);
+            if x_root == y_root {
+                return false;
+            }
+
+            let (child, parent) = if x_root < y_root {
+                (x_root, y_root)
+            } else {
+                (y_root, x_root)
+            };
+            if self.parents[child]
+                .compare_exchange(child, parent, Ordering::AcqRel, Ordering::Acquire)
+                .is_ok()
+            {
+                self.sets.fetch_sub(1, Ordering::AcqRel);
+                return true;
+            }
+        }
+    }
+
+    pub fn same_set(&self, x: usize, y: usize) -> bool {
+        loop {
+            let x_root = self.find(x);
+            let y_root = self.find(y);
+            if x_root == y_root {
+                return true;
+            }
+            // x_root may have been linked under y_root in between.
+            if self.parent(x_root) == x_root {
+                return false;
+            }
+        }
+    }
+
+    pub fn count_sets(&self) -> usize {
+        self.sets.load(Ordering::Acquire)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -113,4 +597,220 @@ mod tests {
 
         assert_eq!(union5_leader, union2_leader);
     }
+
+    #[test]
+    fn disjoint_set_works() {
+        let mut sets = DisjointSet::new(6);
+        assert_eq!(sets.count_sets(), 6);
+        assert!(!sets.same_set(0, 1));
+
+        assert!(sets.union(0, 1));
+        assert!(sets.union(2, 1));
+        assert!(!sets.union(0, 2));
+        assert!(sets.union(4, 5));
+
+        assert!(sets.same_set(2, 0));
+        assert!(!sets.same_set(3, 0));
+        assert_eq!(sets.set_size(1), 3);
+        assert_eq!(sets.set_size(3), 1);
+        assert_eq!(sets.count_sets(), 3);
+
+        assert!(sets.union(5, 0));
+        assert_eq!(sets.set_size(4), 5);
+        assert_eq!(sets.count_sets(), 2);
+    }
+
+    #[test]
+    fn disjoint_set_should_compress_long_chains() {
+        let size = 100_000;
+        let mut sets = DisjointSet::new(size);
+        for i in 1..size {
+            sets.union(i - 1, i);
+        }
+
+        let root = sets.find(0);
+        assert!((0..size).all(|i| sets.find(i) == root));
+        assert_eq!(sets.set_size(size - 1), size);
+        assert_eq!(sets.count_sets(), 1);
+    }
+
+    #[test]
+    fn aggregate_disjoint_set_works() {
+        let values = [5, -2, 7, 1, 4];
+        let mut sets = AggregateDisjointSet::new(
+            values.iter().map(|&v| (v, v, v)).collect(),
+            |a: (isize, isize, isize), b: (isize, isize, isize)| {
+                (a.0 + b.0, a.1.min(b.1), a.2.max(b.2))
+            },
+        );
+
+        assert!(sets.union(0, 1));
+        assert!(sets.union(3, 2));
+        assert!(!sets.union(1, 0));
+        assert_eq!(*sets.aggregate(1), (3, -2, 5));
+        assert_eq!(*sets.aggregate(2), (8, 1, 7));
+        assert_eq!(*sets.aggregate(4), (4, 4, 4));
+
+        assert!(sets.union(2, 0));
+        assert_eq!(*sets.aggregate(4), (4, 4, 4));
+        assert_eq!(*sets.aggregate(3), (11, -2, 7));
+        assert_eq!(sets.set_size(0), 4);
+        assert_eq!(sets.count_sets(), 2);
+    }
+
+    #[test]
+    fn aggregate_disjoint_set_should_keep_merge_order() {
+        let mut sets = AggregateDisjointSet::new((0..5).map(|i| vec![i]).collect(), |mut a, b| {
+            a.extend(b);
+            a
+        });
+
+        sets.union(1, 2);
+        sets.union(4, 3);
+        sets.union(4, 1);
+        sets.union(0, 3);
+        assert_eq!(*sets.aggregate(2), vec![0, 4, 3, 1, 2]);
+    }
+
+    #[test]
+    fn rollback_union_find_works() {
+        let mut sets = RollbackUnionFind::new(5);
+        assert!(sets.union(0, 1));
+        let snapshot = sets.snapshot();
+
+        assert!(sets.union(2, 3));
+        assert!(!sets.union(3, 2));
+        assert!(sets.union(1, 3));
+        assert!(sets.same_set(0, 2));
+        assert_eq!(sets.set_size(3), 4);
+        assert_eq!(sets.count_sets(), 2);
+
+        sets.rollback(snapshot);
+        assert!(sets.same_set(0, 1));
+        assert!(!sets.same_set(0, 2));
+        assert!(!sets.same_set(2, 3));
+        assert_eq!(sets.set_size(0), 2);
+        assert_eq!(sets.count_sets(), 4);
+
+        assert!(sets.undo());
+        assert!(!sets.undo());
+        assert_eq!(sets.count_sets(), 5);
+    }
+
+    #[test]
+    fn rollback_should_restore_exact_state() {
+        let size = 64;
+        let mut sets = RollbackUnionFind::new(size);
+        let mut states = Vec::new();
+
+        for i in 0..size * 2 {
+            states.push((sets.snapshot(), sets.parents.clone(), sets.ranks.clone()));
+            sets.union((i * 7) % size, (i * 13 + 5) % size);
+        }
+
+        while let Some((snapshot, parents, ranks)) = states.pop() {
+            sets.rollback(snapshot);
+            assert_eq!(sets.parents, parents);
+            assert_eq!(sets.ranks, ranks);
+        }
+        assert_eq!(sets.count_sets(), size);
+        assert!((0..size).all(|i| sets.set_size(i) == 1));
+    }
+
+    #[test]
+    fn potential_union_find_works() {
+        let mut sets = PotentialUnionFind::new(6);
+        assert_eq!(sets.union(0, 1, 3), Ok(true));
+        assert_eq!(sets.union(2, 1, -4), Ok(true));
+        assert_eq!(sets.union(3, 4, 10), Ok(true));
+        assert_eq!(sets.diff(0, 2), Some(7));
+        assert_eq!(sets.diff(2, 0), Some(-7));
+        assert_eq!(sets.diff(0, 3), None);
+
+        assert_eq!(sets.union(4, 2, 1), Ok(true));
+        assert_eq!(sets.diff(3, 0), Some(4));
+        assert_eq!(sets.diff(5, 5), Some(0));
+        assert_eq!(sets.union(3, 1, 7), Ok(false));
+        assert_eq!(
+            sets.union(1, 3, 7),
+            Err(PotentialConflict {
+                known: -7,
+                requested: 7,
+            })
+        );
+        assert_eq!(sets.set_size(0), 5);
+        assert_eq!(sets.count_sets(), 2);
+    }
+
+    #[test]
+    fn potential_union_find_should_match_values() {
+        let size = 200;
+        let values: Vec<isize> = (0..size).map(|i| (i * 37 % 101) as isize - 50).collect();
+        let mut sets = PotentialUnionFind::new(size);
+
+        for i in 0..size {
+            let (x, y) = (i, (i * 17 + 3) % size);
+            assert!(sets.union(x, y, values[x] - values[y]).is_ok());
+        }
+        for i in 1..size {
+            if let Some(d) = sets.diff(i, i - 1) {
+                assert_eq!(d, values[i] - values[i - 1]);
+            }
+        }
+    }
+
+    #[test]
+    fn concurrent_disjoint_set_works() {
+        let sets = ConcurrentDisjointSet::new(5);
+        assert!(sets.union(0, 1));
+        assert!(sets.union(3, 1));
+        assert!(!sets.union(0, 3));
+        assert!(sets.same_set(3, 0));
+        assert!(!sets.same_set(2, 0));
+        assert_eq!(sets.count_sets(), 3);
+    }
+
+    #[test]
+    fn concurrent_disjoint_set_should_match_sequential() {
+        let size = 20_000;
+        let threads = 8;
+        let pairs: Vec<(usize, usize)> = (0..size * 3 / 4)
+            .map(|i| ((i * 7919) % size, (i * 104_729 + 13) % size))
+            .collect();
+
+        let mut expected = DisjointSet::new(size);
+        for &(x, y) in pairs.iter() {
+            expected.union(x, y);
+        }
+
+        for _ in 0..4 {
+            let sets = ConcurrentDisjointSet::new(size);
+            let merged = AtomicUsize::new(0);
+            std::thread::scope(|s| {
+                for t in 0..threads {
+                    let (sets, merged, pairs) = (&sets, &merged, &pairs);
+                    s.spawn(move || {
+                        // Every thread sees all pairs in its own order.
+                        for k in 0..pairs.len() {
+                            let (x, y) = pairs[(k * (2 * t + 1)) % pairs.len()];
+                            if sets.union(x, y) {
+                                merged.fetch_add(1, Ordering::Relaxed);
+                            }
+                            sets.find((x + y) % size);
+                        }
+                    });
+                }
+            });
+
+            assert_eq!(sets.count_sets(), expected.count_sets());
+            assert_eq!(merged.load(Ordering::Relaxed), size - expected.count_sets());
+            for i in 1..size {
+                assert_eq!(sets.same_set(i, i - 1), expected.same_set(i, i - 1));
+                assert_eq!(
+                    sets.same_set(i, pairs[i % pairs.len()].0),
+                    expected.same_set(i, pairs[i % pairs.len()].0)
+                );
+            }
+        }
+    }
 }
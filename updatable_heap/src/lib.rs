@@ -1,4 +1,5 @@
 const BIGGEST_NUMBER: isize = isize::MAX;
+const REMOVED_IDX: usize = usize::MAX;
 
 #[derive(Debug)]
 pub struct UpdateableHeapElem<Payload: Default + Clone> {
@@ -50,16 +51,33 @@ impl<Payload: Default + Clone> UpdateableHeap<Payload> {
         let idx = elem.idx;
         self.data.push(elem);
         self.size += 1;
+        self.idx_map[idx] = self.size - 1;
 
         if self.size == 1 {
             return 0;
         }
 
-        self.idx_map[idx] = self.size - 1;
-
         self.up_fn(self.size - 1)
     }
 
+    // Inserts an element under the next free index, growing the capacity by
+    // one. Handy when the number of elements isn't known up front.
+    pub fn push(&mut self, ordering_key: isize, payload: Payload) -> usize {
+        let idx = self.capacity;
+        self.capacity += 1;
+        self.idx_map.push(REMOVED_IDX);
+        self.insert(UpdateableHeapElem::new(idx, ordering_key, payload));
+        idx
+    }
+
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
     pub fn decrease_by_idx(&mut self, idx: usize, new_val: isize, payload: Payload) -> usize {
         let data_idx = self.idx_map[idx];
         if data_idx >= self.capacity {
@@ -80,9 +98,9 @@ impl<Payload: Default + Clone> UpdateableHeap<Payload> {
         self.data.swap(0, self.size - 1);
         let result = self.data.pop().unwrap();
         self.size -= 1;
+        self.idx_map[result.idx] = REMOVED_IDX;
 
         if self.size > 0 {
-            self.idx_map[result.idx] = self.capacity;
             let new_head = self.data[0].idx;
             self.idx_map[new_head] = 0;
             self.down_fn(0);
@@ -159,3 +177,27 @@ impl<Payload: Default + Clone> UpdateableHeap<Payload> {
         result
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_should_grow() {
+        let mut heap: UpdateableHeap<char> = UpdateableHeap::new(0);
+        assert_eq!(heap.push(5, 'a'), 0);
+        assert_eq!(heap.push(3, 'b'), 1);
+        assert_eq!(heap.push(7, 'c'), 2);
+        heap.decrease_by_idx(2, 1, 'd');
+        assert_eq!(heap.len(), 3);
+
+        let elem = heap.get_and_remove_min();
+        assert_eq!((elem.idx, elem.ordering_key, elem.payload), (2, 1, 'd'));
+        heap.decrease_by_idx(2, 0, 'e');
+        assert_eq!(heap.push(4, 'f'), 3);
+
+        let order: Vec<usize> = (0..3).map(|_| heap.get_and_remove_min().idx).collect();
+        assert_eq!(order, vec![1, 3, 0]);
+        assert!(heap.is_empty());
+    }
+}